@@ -0,0 +1,33 @@
+// CRT filter: screen curvature, scanlines and vignette, see `graphics/post_process.rs`.
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+struct CrtSettings {
+    scanline_intensity: f32,
+    scanline_count: f32,
+    curvature: f32,
+    vignette: f32,
+}
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var screen_sampler: sampler;
+@group(0) @binding(2) var<uniform> settings: CrtSettings;
+
+const PI: f32 = 3.14159265;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // bend the screen outwards, stronger towards the corners
+    let centered = in.uv * 2.0 - 1.0;
+    let warped = centered * (1.0 + centered.yx * centered.yx * settings.curvature);
+    let uv = warped * 0.5 + 0.5;
+
+    var color = textureSample(screen_texture, screen_sampler, uv).rgb;
+
+    let scanline = 0.5 + 0.5 * sin(uv.y * settings.scanline_count * PI);
+    color *= 1.0 - settings.scanline_intensity * scanline;
+    color *= 1.0 - settings.vignette * dot(centered, centered) / 2.0;
+
+    // outside of the bent screen is black
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+    return vec4<f32>(select(vec3<f32>(0.0), color, inside), 1.0);
+}
@@ -0,0 +1,46 @@
+// Sprite sheet material with hit flash, tint, dissolve and opacity, see `graphics/effects.rs`.
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+struct EffectParams {
+    tint: vec4<f32>,
+    flash: f32,
+    dissolve: f32,
+    opacity: f32,
+    frame: u32,
+    frames: u32,
+}
+
+@group(2) @binding(0) var<uniform> params: EffectParams;
+@group(2) @binding(1) var sheet_texture: texture_2d<f32>;
+@group(2) @binding(2) var sheet_sampler: sampler;
+
+// Width of the glowing rim at the dissolve threshold
+const DISSOLVE_EDGE: f32 = 0.08;
+const DISSOLVE_EDGE_COLOR: vec3<f32> = vec3<f32>(1.0, 0.55, 0.15);
+
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    // all sheets are a single row of frames
+    let uv = vec2<f32>((mesh.uv.x + f32(params.frame)) / f32(params.frames), mesh.uv.y);
+    var color = textureSample(sheet_texture, sheet_sampler, uv);
+
+    var rgb = mix(color.rgb, params.tint.rgb, params.tint.a);
+    rgb = mix(rgb, vec3<f32>(1.0), params.flash);
+
+    if params.dissolve > 0.0 {
+        // blocky noise, so the sprite crumbles into pieces
+        let noise = hash(floor(mesh.uv * 16.0));
+        if noise < params.dissolve {
+            discard;
+        }
+        if noise < params.dissolve + DISSOLVE_EDGE {
+            rgb = DISSOLVE_EDGE_COLOR;
+        }
+    }
+
+    return vec4<f32>(rgb, color.a * params.opacity);
+}
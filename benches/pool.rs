@@ -0,0 +1,89 @@
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use criterion::{Criterion, criterion_group, criterion_main};
+use spacedogs::pool::{Pool, PoolCommands, Poolable};
+
+/// Number of entities spawned and released per iteration, roughly a few seconds of heavy fire.
+const N: usize = 2000;
+
+#[derive(Component)]
+struct Bullet;
+
+impl Poolable for Bullet {}
+
+fn bundle(i: usize) -> impl Bundle {
+    (
+        Bullet,
+        Name::new("Bullet"),
+        Transform::from_xyz(i as f32, 0., 0.),
+        Visibility::default(),
+    )
+}
+
+fn spawn_despawn(c: &mut Criterion) {
+    let mut world = World::new();
+    let mut state = SystemState::<(Commands, Query<Entity, With<Bullet>>)>::new(&mut world);
+
+    c.bench_function("spawn_despawn", |b| {
+        b.iter(|| {
+            let (mut commands, _) = state.get_mut(&mut world);
+            for i in 0..N {
+                commands.spawn(bundle(i));
+            }
+            state.apply(&mut world);
+
+            let (mut commands, bullets) = state.get_mut(&mut world);
+            for bullet in &bullets {
+                commands.entity(bullet).despawn();
+            }
+            state.apply(&mut world);
+        })
+    });
+}
+
+type PooledState = SystemState<(
+    PoolCommands<'static, 'static, Bullet>,
+    Query<'static, 'static, Entity, With<Bullet>>,
+)>;
+
+/// Spawns `N` pooled bullets and releases them again.
+fn spawn_release(world: &mut World, state: &mut PooledState) {
+    let (mut pool, _) = state.get_mut(world);
+    for i in 0..N {
+        pool.spawn(bundle(i));
+    }
+    state.apply(world);
+
+    let (mut pool, bullets) = state.get_mut(world);
+    for bullet in &bullets {
+        pool.release(bullet);
+    }
+    state.apply(world);
+    world.resource_mut::<Pool<Bullet>>().recycle();
+}
+
+fn pooled(c: &mut Criterion) {
+    let mut world = World::new();
+    world.init_resource::<Pool<Bullet>>();
+    let mut state = PooledState::new(&mut world);
+
+    // the first round fills the pool, all later ones only reuse its entities
+    spawn_release(&mut world, &mut state);
+    let entities = world.entities().len();
+
+    c.bench_function("pooled", |b| {
+        b.iter(|| spawn_release(&mut world, &mut state))
+    });
+
+    let pool = world.resource::<Pool<Bullet>>();
+    assert_eq!(pool.active(), 0);
+    assert_eq!(pool.free(), N);
+    assert_eq!(
+        world.entities().len(),
+        entities,
+        "entities are reused, not allocated again"
+    );
+}
+
+criterion_group!(benches, spawn_despawn, pooled);
+criterion_main!(benches);
@@ -0,0 +1,406 @@
+/// Music and sound effects: stage dependent music layers and pooled sound effects triggered by events.
+use std::collections::HashMap;
+
+use bevy::audio::Volume;
+use serde::{Deserialize, Serialize};
+use synth::{Pattern, SynthParams};
+
+use crate::prelude::constants::*;
+use crate::prelude::*;
+use crate::settings::Settings;
+use crate::world::bomb::BombDetonatedEvent;
+use crate::world::pickup::PickupCollectedEvent;
+use crate::world::player::PlayerHitByEnemyEvent;
+use crate::world::stage::Stage;
+
+pub mod synth;
+
+/// Synthesizer parameters of every sound effect.
+const SFX_PARAMS: &str = include_str!("../assets/audio/sfx.ron");
+/// Tempo and notes of the music layers.
+const MUSIC_PARAMS: &str = include_str!("../assets/audio/music.ron");
+
+/// Volume channels in `0..=1`, music and sound effects are scaled by the master volume.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            master: 0.8,
+            music: 0.6,
+            sfx: 0.8,
+        }
+    }
+}
+
+impl AudioSettings {
+    fn music_volume(&self) -> f32 {
+        self.master * self.music
+    }
+
+    fn sfx_volume(&self) -> f32 {
+        self.master * self.sfx
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sfx {
+    Shoot,
+    EnemyHit,
+    Explosion,
+    PlayerHit,
+    Pickup,
+    Bomb,
+}
+
+impl Sfx {
+    const ALL: [Sfx; 6] = [
+        Sfx::Shoot,
+        Sfx::EnemyHit,
+        Sfx::Explosion,
+        Sfx::PlayerHit,
+        Sfx::Pickup,
+        Sfx::Bomb,
+    ];
+
+    /// Voices the effect may use at once, further plays cut off its oldest voice.
+    fn max_voices(&self) -> usize {
+        match self {
+            Sfx::Shoot => 4,
+            Sfx::EnemyHit => 3,
+            Sfx::Explosion => 4,
+            Sfx::PlayerHit => 1,
+            Sfx::Pickup => 2,
+            Sfx::Bomb => 1,
+        }
+    }
+}
+
+/// Plays a sound effect, if a voice is available.
+#[derive(Event)]
+pub struct PlaySfx(pub Sfx);
+
+/// Loops of `beats` at `bpm`, all layers have the same length so they play in sync.
+#[derive(Deserialize)]
+struct MusicParams {
+    bpm: f32,
+    beats: f32,
+    layers: Vec<MusicLayerParams>,
+}
+
+/// A loop fading in once the game reaches `stage`.
+#[derive(Deserialize)]
+struct MusicLayerParams {
+    stage: usize,
+    patterns: Vec<Pattern>,
+}
+
+#[derive(Resource)]
+struct SoundBank {
+    sfx: Vec<(Sfx, Handle<AudioSource>)>,
+    /// Music layers and the stage they fade in at
+    music: Vec<(usize, Handle<AudioSource>)>,
+}
+
+/// Sound effects and music are synthesized once at startup.
+impl FromWorld for SoundBank {
+    fn from_world(world: &mut World) -> Self {
+        let params: HashMap<Sfx, SynthParams> =
+            ron::from_str(SFX_PARAMS).expect("sound effect parameters are valid");
+        let music: MusicParams = ron::from_str(MUSIC_PARAMS).expect("music parameters are valid");
+        let mut sources = world.resource_mut::<Assets<AudioSource>>();
+        SoundBank {
+            music: music
+                .layers
+                .iter()
+                .map(|layer| {
+                    let samples = synth::sequence(&layer.patterns, music.bpm, music.beats);
+                    let source = AudioSource {
+                        bytes: synth::wav(&samples).into(),
+                    };
+                    (layer.stage, sources.add(source))
+                })
+                .collect(),
+            sfx: Sfx::ALL
+                .iter()
+                .map(|sfx| {
+                    let samples = synth::synthesize(&params[sfx]);
+                    let source = AudioSource {
+                        bytes: synth::wav(&samples).into(),
+                    };
+                    (*sfx, sources.add(source))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl SoundBank {
+    fn sfx(&self, sfx: Sfx) -> Handle<AudioSource> {
+        self.sfx
+            .iter()
+            .find(|(s, _)| *s == sfx)
+            .map(|(_, handle)| handle.clone())
+            .expect("every sound effect is loaded")
+    }
+}
+
+#[derive(Debug)]
+struct Voice {
+    entity: Entity,
+    /// Effect and start time, while the voice is playing
+    playing: Option<(Sfx, f32)>,
+}
+
+/// A fixed number of audio entities sound effects are played on,
+/// so rapid fire does not spawn an audio entity per shot.
+#[derive(Resource, Default, Debug)]
+struct VoicePool {
+    voices: Vec<Voice>,
+}
+
+impl VoicePool {
+    /// The voice to play `sfx` on: the oldest voice of `sfx` once it reached its limit,
+    /// otherwise a free voice or the oldest voice of all.
+    fn pick(&self, sfx: Sfx) -> Option<usize> {
+        let oldest = |filter: &dyn Fn(Sfx) -> bool| {
+            self.voices
+                .iter()
+                .enumerate()
+                .filter_map(|(i, voice)| match voice.playing {
+                    Some((s, started)) if filter(s) => Some((i, started)),
+                    _ => None,
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(i, _)| i)
+        };
+
+        let playing = self
+            .voices
+            .iter()
+            .filter(|voice| voice.playing.is_some_and(|(s, _)| s == sfx))
+            .count();
+        if playing >= sfx.max_voices() {
+            return oldest(&|s| s == sfx);
+        }
+        self.voices
+            .iter()
+            .position(|voice| voice.playing.is_none())
+            .or_else(|| oldest(&|_| true))
+    }
+}
+
+/// Volume of a music layer while it fades in or out, in `0..=1`.
+#[derive(Component)]
+struct MusicLayer {
+    stage: usize,
+    volume: f32,
+}
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundBank>()
+            .init_resource::<VoicePool>()
+            .add_event::<PlaySfx>()
+            .add_systems(Startup, (spawn_voices, spawn_music))
+            .add_systems(
+                Update,
+                (
+                    (
+                        on_enemy_hit_sfx,
+                        on_enemy_died_sfx,
+                        on_player_hit_sfx,
+                        on_bomb_sfx,
+                        on_pickup_sfx,
+                    ),
+                    play_sfx,
+                    fade_music,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn spawn_voices(mut commands: Commands, mut pool: ResMut<VoicePool>) {
+    pool.voices = (0..MAX_SFX_VOICES)
+        .map(|_| Voice {
+            entity: commands.spawn(Name::new("SfxVoice")).id(),
+            playing: None,
+        })
+        .collect();
+}
+
+fn spawn_music(mut commands: Commands, bank: Res<SoundBank>) {
+    for (stage, source) in &bank.music {
+        commands.spawn((
+            Name::new("MusicLayer"),
+            MusicLayer {
+                stage: *stage,
+                volume: 0.,
+            },
+            AudioPlayer::new(source.clone()),
+            // silent layers keep playing, so they stay in sync with the others
+            PlaybackSettings::LOOP.with_volume(Volume::ZERO),
+        ));
+    }
+}
+
+fn on_enemy_hit_sfx(mut events: EventReader<EnemyHitEvent>, mut sfx: EventWriter<PlaySfx>) {
+    for _ in events.read() {
+        sfx.send(PlaySfx(Sfx::EnemyHit));
+    }
+}
+
+fn on_enemy_died_sfx(mut events: EventReader<EnemyDiedEvent>, mut sfx: EventWriter<PlaySfx>) {
+    for _ in events.read() {
+        sfx.send(PlaySfx(Sfx::Explosion));
+    }
+}
+
+fn on_player_hit_sfx(
+    mut events: EventReader<PlayerHitByEnemyEvent>,
+    mut sfx: EventWriter<PlaySfx>,
+) {
+    for _ in events.read() {
+        sfx.send(PlaySfx(Sfx::PlayerHit));
+    }
+}
+
+fn on_bomb_sfx(mut events: EventReader<BombDetonatedEvent>, mut sfx: EventWriter<PlaySfx>) {
+    for _ in events.read() {
+        sfx.send(PlaySfx(Sfx::Bomb));
+    }
+}
+
+fn on_pickup_sfx(mut events: EventReader<PickupCollectedEvent>, mut sfx: EventWriter<PlaySfx>) {
+    for _ in events.read() {
+        sfx.send(PlaySfx(Sfx::Pickup));
+    }
+}
+
+fn play_sfx(
+    mut commands: Commands,
+    mut events: EventReader<PlaySfx>,
+    mut pool: ResMut<VoicePool>,
+    bank: Res<SoundBank>,
+    settings: Res<Settings>,
+    voices: Query<(Has<AudioPlayer>, Option<&AudioSink>)>,
+    time: Res<Time<Real>>,
+) {
+    // finished voices lose their player, see `PlaybackSettings::REMOVE`
+    for voice in &mut pool.voices {
+        if let Ok((false, _)) = voices.get(voice.entity) {
+            voice.playing = None;
+        }
+    }
+
+    for PlaySfx(sfx) in events.read() {
+        let Some(index) = pool.pick(*sfx) else {
+            continue;
+        };
+        let voice = &mut pool.voices[index];
+        if let Ok((_, Some(sink))) = voices.get(voice.entity) {
+            sink.stop();
+        }
+        voice.playing = Some((*sfx, time.elapsed_secs()));
+
+        let volume = settings.audio.sfx_volume();
+        commands
+            .entity(voice.entity)
+            .remove::<(AudioPlayer, AudioSink, PlaybackSettings)>()
+            .insert((
+                AudioPlayer::new(bank.sfx(*sfx)),
+                PlaybackSettings::REMOVE.with_volume(Volume::new(volume)),
+            ));
+    }
+}
+
+/// Fades each layer towards full volume once its stage is reached. Runs on real time,
+/// so the music keeps its volume while the game is paused.
+fn fade_music(
+    stage: Res<Stage>,
+    settings: Res<Settings>,
+    mut layers: Query<(&mut MusicLayer, Option<&AudioSink>)>,
+    time: Res<Time<Real>>,
+) {
+    let step = time.delta_secs() / MUSIC_FADE_SECS;
+    for (mut layer, sink) in &mut layers {
+        let target = if stage.number >= layer.stage { 1. } else { 0. };
+        let volume = layer.volume + (target - layer.volume).clamp(-step, step);
+        if volume != layer.volume {
+            layer.volume = volume;
+        }
+        // the sink exists once the loop is loaded
+        if let Some(sink) = sink {
+            let volume = layer.volume * settings.audio.music_volume();
+            if sink.volume() != volume {
+                sink.set_volume(volume);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(playing: &[Option<(Sfx, f32)>]) -> VoicePool {
+        VoicePool {
+            voices: playing
+                .iter()
+                .enumerate()
+                .map(|(i, playing)| Voice {
+                    entity: Entity::from_raw(i as u32),
+                    playing: *playing,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn every_sfx_has_params() {
+        let params: HashMap<Sfx, SynthParams> = ron::from_str(SFX_PARAMS).unwrap();
+        for sfx in Sfx::ALL {
+            assert!(params.contains_key(&sfx), "{sfx:?} has no parameters");
+        }
+    }
+
+    #[test]
+    fn music_notes_are_within_the_loop() {
+        let music: MusicParams = ron::from_str(MUSIC_PARAMS).unwrap();
+        assert!(!music.layers.is_empty());
+        for layer in &music.layers {
+            for pattern in &layer.patterns {
+                assert!(!pattern.notes.is_empty());
+                for (beat, _) in &pattern.notes {
+                    assert!((0. ..music.beats).contains(beat), "note at beat {beat}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn voices_are_reused() {
+        // a free voice is used first
+        let free = pool(&[Some((Sfx::Shoot, 0.)), None]);
+        assert_eq!(free.pick(Sfx::Shoot), Some(1));
+
+        // at its limit, the oldest voice of the effect is cut off
+        let limited = pool(&[Some((Sfx::PlayerHit, 1.)), Some((Sfx::Explosion, 0.)), None]);
+        assert_eq!(limited.pick(Sfx::PlayerHit), Some(0));
+
+        // all voices busy, the oldest is cut off
+        let busy = pool(&[Some((Sfx::Shoot, 2.)), Some((Sfx::Explosion, 1.))]);
+        assert_eq!(busy.pick(Sfx::Pickup), Some(1));
+
+        assert_eq!(pool(&[]).pick(Sfx::Shoot), None);
+    }
+}
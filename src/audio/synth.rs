@@ -0,0 +1,261 @@
+/// A small sfxr style synthesizer, sound effects and music are generated from `SynthParams` instead of shipped as files.
+use std::f32::consts::TAU;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Deserialize;
+
+const SAMPLE_RATE: u32 = 44100;
+/// Noise values per period of the noise wave
+const NOISE_STEPS: usize = 32;
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Waveform {
+    Square,
+    Sawtooth,
+    Sine,
+    Noise,
+}
+
+/// Parameters of a sound, durations are in seconds and frequencies in Hz.
+/// The same parameters always give the same sound, the noise is generated from `seed`.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SynthParams {
+    pub wave: Waveform,
+    pub seed: u64,
+    pub attack: f32,
+    pub sustain: f32,
+    /// Extra volume at the start of the sustain, fading out over it
+    pub punch: f32,
+    pub decay: f32,
+    pub frequency: f32,
+    /// Change of the frequency in octaves per second
+    pub slide: f32,
+    /// The sound stops once the frequency slides below it
+    pub min_frequency: f32,
+    /// Relative change of the frequency
+    pub vibrato_depth: f32,
+    pub vibrato_speed: f32,
+    /// The frequency is multiplied by `arpeggio` after `arpeggio_time`
+    pub arpeggio: f32,
+    pub arpeggio_time: f32,
+    /// Fraction of a square wave period that is high, changed by `duty_sweep` per second
+    pub duty: f32,
+    pub duty_sweep: f32,
+    /// Cutoff of the low pass filter in `0..=1`, 1 lets everything pass
+    pub low_pass: f32,
+    /// Cutoff of the high pass filter in `0..=1`, 0 lets everything pass
+    pub high_pass: f32,
+    pub volume: f32,
+}
+
+impl Default for SynthParams {
+    fn default() -> Self {
+        SynthParams {
+            wave: Waveform::Square,
+            seed: 0,
+            attack: 0.,
+            sustain: 0.1,
+            punch: 0.,
+            decay: 0.2,
+            frequency: 440.,
+            slide: 0.,
+            min_frequency: 0.,
+            vibrato_depth: 0.,
+            vibrato_speed: 0.,
+            arpeggio: 1.,
+            arpeggio_time: 0.,
+            duty: 0.5,
+            duty_sweep: 0.,
+            low_pass: 1.,
+            high_pass: 0.,
+            volume: 0.5,
+        }
+    }
+}
+
+impl SynthParams {
+    fn envelope(&self, t: f32) -> f32 {
+        if t < self.attack {
+            t / self.attack
+        } else if t < self.attack + self.sustain {
+            1. + self.punch * (1. - (t - self.attack) / self.sustain)
+        } else {
+            (1. - (t - self.attack - self.sustain) / self.decay).max(0.)
+        }
+    }
+
+    fn duration(&self) -> f32 {
+        self.attack + self.sustain + self.decay
+    }
+}
+
+/// Generates the samples of a sound, mono at `SAMPLE_RATE`.
+pub fn synthesize(params: &SynthParams) -> Vec<f32> {
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let mut noise = [0.; NOISE_STEPS];
+    noise.fill_with(|| rng.random_range(-1.0..=1.0));
+
+    let dt = 1. / SAMPLE_RATE as f32;
+    let len = (params.duration() * SAMPLE_RATE as f32) as usize;
+    let mut samples = Vec::with_capacity(len);
+    let mut frequency = params.frequency;
+    let mut phase = 0.;
+    let mut low_pass = 0.;
+    let mut high_pass = 0.;
+    let mut last = 0.;
+
+    for i in 0..len {
+        let t = i as f32 * dt;
+        frequency *= (params.slide * dt).exp2();
+        if frequency < params.min_frequency {
+            break;
+        }
+        let arpeggio = if params.arpeggio_time > 0. && t >= params.arpeggio_time {
+            params.arpeggio
+        } else {
+            1.
+        };
+        let vibrato = 1. + (t * params.vibrato_speed * TAU).sin() * params.vibrato_depth;
+
+        phase += frequency * arpeggio * vibrato * dt;
+        if phase >= 1. {
+            phase %= 1.;
+            if params.wave == Waveform::Noise {
+                noise.fill_with(|| rng.random_range(-1.0..=1.0));
+            }
+        }
+
+        let duty = (params.duty + params.duty_sweep * t).clamp(0.05, 0.5);
+        let value = match params.wave {
+            Waveform::Square if phase < duty => 1.,
+            Waveform::Square => -1.,
+            Waveform::Sawtooth => 2. * phase - 1.,
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Noise => noise[(phase * NOISE_STEPS as f32) as usize % NOISE_STEPS],
+        };
+
+        // one pole filters, the high pass removes what the low pass lets through slowly
+        low_pass += params.low_pass * (value - low_pass);
+        high_pass = (1. - params.high_pass) * (high_pass + low_pass - last);
+        last = low_pass;
+
+        let sample = high_pass * params.envelope(t) * params.volume;
+        samples.push(sample.clamp(-1., 1.));
+    }
+    samples
+}
+
+/// A sound played at each note, notes are `(beat, frequency)`.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Pattern {
+    pub sound: SynthParams,
+    pub notes: Vec<(f32, f32)>,
+}
+
+/// Mixes `patterns` into a loop of `beats` at `bpm`. Notes ringing past its end continue
+/// at its start, so the loop repeats without a gap.
+pub fn sequence(patterns: &[Pattern], bpm: f32, beats: f32) -> Vec<f32> {
+    let samples_per_beat = 60. / bpm * SAMPLE_RATE as f32;
+    let mut samples = vec![0.; (beats * samples_per_beat) as usize];
+    let len = samples.len();
+    for pattern in patterns {
+        for (beat, frequency) in &pattern.notes {
+            let note = synthesize(&SynthParams {
+                frequency: *frequency,
+                ..pattern.sound.clone()
+            });
+            let start = (beat * samples_per_beat) as usize;
+            for (i, sample) in note.into_iter().enumerate() {
+                samples[(start + i) % len] += sample;
+            }
+        }
+    }
+    for sample in &mut samples {
+        *sample = sample.clamp(-1., 1.);
+    }
+    samples
+}
+
+/// Encodes samples of `synthesize` as a 16 bit PCM wav file, which Bevy can decode as an `AudioSource`.
+pub fn wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    // format chunk: PCM, mono, sample rate, byte rate, block align, bits per sample
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        bytes.extend_from_slice(&((sample * i16::MAX as f32) as i16).to_le_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synthesis_is_deterministic() {
+        let params = SynthParams {
+            wave: Waveform::Noise,
+            seed: 42,
+            ..SynthParams::default()
+        };
+        let samples = synthesize(&params);
+        assert_eq!(samples.len(), (0.3 * SAMPLE_RATE as f32) as usize);
+        assert_eq!(samples, synthesize(&params));
+        assert_ne!(
+            samples,
+            synthesize(&SynthParams {
+                seed: 43,
+                ..params.clone()
+            })
+        );
+    }
+
+    #[test]
+    fn notes_wrap_around_the_loop() {
+        let pattern = Pattern {
+            sound: SynthParams {
+                wave: Waveform::Sine,
+                sustain: 1.,
+                decay: 0.,
+                ..SynthParams::default()
+            },
+            notes: vec![(1.5, 440.)],
+        };
+        // two beats of a second each, the note starts halfway through the second and ends in the first
+        let samples = sequence(&[pattern], 60., 2.);
+        assert_eq!(samples.len(), 2 * SAMPLE_RATE as usize);
+        assert!(
+            samples[..SAMPLE_RATE as usize / 2 - 1]
+                .iter()
+                .any(|s| *s != 0.)
+        );
+        assert!(
+            samples[SAMPLE_RATE as usize..3 * SAMPLE_RATE as usize / 2]
+                .iter()
+                .all(|s| *s == 0.)
+        );
+    }
+
+    #[test]
+    fn wav_has_header_and_data() {
+        let bytes = wav(&[0., 1., -1.]);
+        assert_eq!(bytes.len(), 44 + 6);
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(&bytes[44..46], &0i16.to_le_bytes());
+        assert_eq!(&bytes[46..48], &i16::MAX.to_le_bytes());
+    }
+}
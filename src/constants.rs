@@ -2,11 +2,19 @@ use crate::prelude::*;
 
 pub const VIEWPORT_WIDTH: f32 = 1920.;
 pub const VIEWPORT_HEIGHT: f32 = 1080.;
+// Render layer nothing is drawn on, see `graphics::display`.
+pub const LETTERBOX_RENDER_LAYER: usize = 31;
 // These constants are defined in `Transform` units.
 // Using the default 2D camera they correspond 1:1 with screen pixels.
 pub const PADDLE_SIZE: Vec2 = Vec2::new(120.0, 20.0);
 pub const GAP_BETWEEN_PADDLE_AND_FLOOR: f32 = 60.0;
-pub const PADDLE_SPEED: f32 = 300.0;
+// Ship handling of the player, the terminal speed without input limit is thrust / drag.
+pub const PLAYER_THRUST: f32 = 4000.0;
+pub const PLAYER_DRAG: f32 = 5.5;
+pub const PLAYER_MAX_SPEED: f32 = 600.0;
+// Horizontal speed above which the ship is drawn banking.
+pub const PLAYER_BANK_SPEED: f32 = 150.0;
+pub const PLAYER_SIZE: f32 = 64.0;
 // How close can the paddle get to the wall
 pub const PADDLE_PADDING: f32 = 10.0;
 
@@ -14,7 +22,133 @@ pub const PADDLE_PADDING: f32 = 10.0;
 pub const PROJECTILE_SPEED: f32 = 450.0;
 pub const INITITAL_PROJECTILE_DIRECTION: Vec2 = Vec2::new(0., 1.);
 pub const PROJECTILE_SIZE: f32 = 30.;
-pub const PROJECTILE_COLOR: Color = Color::srgb(1.0, 0.5, 0.5);
+// Colors brighter than 1 glow with bloom enabled.
+pub const PROJECTILE_COLOR: Color = Color::linear_rgb(4.0, 0.8, 0.8);
+// Radians per second a homing missile turns towards its target.
+pub const MISSILE_TURN_RATE: f32 = 3.;
+// Gunners fire at the player every few seconds.
+pub const ENEMY_FIRE_SECS: f32 = 1.5;
+pub const ENEMY_PROJECTILE_SPEED: f32 = 300.;
+pub const ENEMY_PROJECTILE_DAMAGE: f32 = 1.;
+pub const ENEMY_PROJECTILE_COLOR: Color = Color::linear_rgb(4.0, 2.0, 0.3);
+
+// The laser starts at the tip of the ship and reaches across the whole screen.
+pub const LASER_OFFSET: Vec2 = Vec2::new(0., 30.);
+pub const LASER_RANGE: f32 = VIEWPORT_HEIGHT;
+pub const LASER_COLOR: Color = Color::linear_rgb(1.5, 4.0, 4.0);
+pub const LASER_IMPACT_COLOR: Color = Color::linear_rgb(4.0, 4.0, 2.5);
+
+// Sprite animations, see `graphics::animation`.
+pub const EXPLOSION_SIZE: f32 = 96.;
+
+// Enemy movement, see `physics::steering`. Gunners slow down towards a station near the top.
+pub const CREEP_ORBIT_RADIUS: f32 = 250.;
+pub const GUNNER_STATION_Y: f32 = TOP - 250.;
+pub const GUNNER_SLOWING_RADIUS: f32 = 200.;
+
+// Damage feedback, see `graphics::effects`.
+pub const HIT_FLASH_SECS: f32 = 0.06;
+pub const HIT_TINT_COLOR: Color = Color::srgba(1.0, 0.2, 0.1, 0.6);
+pub const HIT_TINT_SECS: f32 = 0.4;
+pub const DISSOLVE_SECS: f32 = 0.6;
+// Blinks per second and the opacity during the faint half of a blink.
+pub const BLINK_FREQUENCY: f32 = 8.;
+pub const BLINK_OPACITY: f32 = 0.2;
+// Materials of ships and wrecks allocated at startup, more are added if ever needed.
+pub const EFFECT_MATERIAL_POOL_SIZE: usize = 32;
+
+// Combat, see `world::combat`. The player is invulnerable for a while after a hit.
+pub const PLAYER_INVULNERABILITY_SECS: f32 = 1.5;
+pub const PLAYER_HEALTH: f32 = 5.;
+pub const PLAYER_CRIT_CHANCE: f32 = 0.1;
+pub const PLAYER_CRIT_MULTIPLIER: f32 = 2.;
+pub const ENEMY_CONTACT_DAMAGE: f32 = 1.;
+pub const ARMOR_MIN_DAMAGE_FRACTION: f32 = 0.2;
+
+// Camera effects, see `graphics::camera`. Shake offset is in units, roll in radians,
+// decays are per second.
+pub const SHAKE_MAX_OFFSET: f32 = 30.;
+pub const SHAKE_MAX_ROLL: f32 = 0.03;
+pub const SHAKE_FREQUENCY: f32 = 25.;
+pub const SHAKE_DECAY: f32 = 1.5;
+pub const ZOOM_PULSE_DECAY: f32 = 0.3;
+pub const HIT_STOP_SPEED: f32 = 0.05;
+pub const ENEMY_DIED_TRAUMA: f32 = 0.3;
+// Additional trauma per point of damage beyond what was needed to kill.
+pub const OVERKILL_TRAUMA: f32 = 0.05;
+pub const ENEMY_DIED_ZOOM: f32 = 0.02;
+pub const PLAYER_HIT_TRAUMA: f32 = 0.6;
+pub const PLAYER_HIT_STOP_SECS: f32 = 0.08;
+// Chromatic aberration on player hits, see `graphics::post_process`.
+pub const PLAYER_HIT_ABERRATION: f32 = 0.06;
+pub const ABERRATION_DECAY: f32 = 0.1;
+
+// Stick tilt ignored around the center, see `input::gamepad`.
+pub const GAMEPAD_DEADZONE: f32 = 0.2;
+pub const PLAYER_HIT_RUMBLE: f32 = 0.6;
+pub const PLAYER_HIT_RUMBLE_SECS: f32 = 0.3;
+
+// Bombs clear the screen, the player is invulnerable while the shockwave spreads.
+pub const BOMB_START_STOCK: u32 = 2;
+pub const BOMB_MAX_STOCK: u32 = 5;
+pub const BOMB_DAMAGE: f32 = 10.;
+pub const BOMB_INVULNERABILITY_SECS: f32 = 2.0;
+pub const BOMB_TRAUMA: f32 = 1.0;
+pub const BOMB_ZOOM: f32 = 0.08;
+pub const BOMB_ABERRATION: f32 = 0.12;
+pub const BOMB_RUMBLE: f32 = 1.0;
+pub const BOMB_RUMBLE_SECS: f32 = 0.6;
+pub const SHOCKWAVE_SECS: f32 = 0.5;
+pub const SHOCKWAVE_RADIUS: f32 = VIEWPORT_WIDTH;
+pub const SHOCKWAVE_COLOR: Color = Color::linear_rgb(3.0, 3.0, 5.0);
+
+// Pickups drift down from where an enemy died, the radius is also their collider size.
+pub const PICKUP_RADIUS: f32 = 14.;
+pub const PICKUP_SPEED: f32 = 120.;
+pub const BOMB_PICKUP_DROP_CHANCE: f32 = 0.1;
+pub const BOMB_PICKUP_COLOR: Color = Color::linear_rgb(4.0, 1.5, 4.0);
+pub const POWER_UP_DROP_CHANCE: f32 = 0.05;
+
+// Power-ups of the player, see `world::power_up`. Durations are in seconds.
+pub const SHIELD_SECS: f32 = 15.;
+pub const SHIELD_HITS: u32 = 2;
+pub const SHIELD_MAX_HITS: u32 = 5;
+pub const SHIELD_INVULNERABILITY_SECS: f32 = 0.5;
+pub const SHIELD_RADIUS: f32 = PLAYER_SIZE * 0.7;
+pub const SHIELD_COLOR: Color = Color::linear_rgb(0.5, 2.0, 4.0);
+pub const SPEED_BOOST_SECS: f32 = 8.;
+pub const SPEED_BOOST_FACTOR: f32 = 1.5;
+pub const SPEED_PICKUP_COLOR: Color = Color::linear_rgb(0.5, 4.0, 1.0);
+pub const RAPID_FIRE_SECS: f32 = 8.;
+pub const RAPID_FIRE_MAX_SECS: f32 = 20.;
+pub const RAPID_FIRE_FACTOR: f32 = 2.;
+pub const RAPID_FIRE_PICKUP_COLOR: Color = Color::linear_rgb(4.0, 3.0, 0.5);
+pub const DAMAGE_BOOST_SECS: f32 = 10.;
+pub const DAMAGE_BOOST_PER_STACK: f32 = 1.;
+pub const DAMAGE_BOOST_MAX_STACKS: u32 = 3;
+pub const DAMAGE_PICKUP_COLOR: Color = Color::linear_rgb(4.0, 0.5, 0.5);
+pub const MAGNET_SECS: f32 = 12.;
+pub const MAGNET_RADIUS: f32 = 400.;
+pub const MAGNET_SPEED: f32 = 500.;
+pub const MAGNET_PICKUP_COLOR: Color = Color::linear_rgb(3.0, 3.0, 3.0);
+
+// Status effects of enemies, see `world::status`. Durations are in seconds.
+pub const BURN_SECS: f32 = 3.;
+pub const BURN_DAMAGE_PER_SEC: f32 = 2.;
+pub const BURN_MAX_STACKS: u32 = 3;
+pub const SLOW_SECS: f32 = 2.;
+pub const SLOW_FACTOR: f32 = 0.4;
+pub const FREEZE_SECS: f32 = 1.5;
+pub const FREEZE_IMMUNITY_SECS: f32 = 2.;
+pub const STUN_SECS: f32 = 1.;
+pub const BURN_TINT_COLOR: Color = Color::srgba(1.0, 0.4, 0.0, 0.5);
+pub const SLOW_TINT_COLOR: Color = Color::srgba(0.3, 0.5, 1.0, 0.4);
+pub const FREEZE_TINT_COLOR: Color = Color::srgba(0.7, 0.95, 1.0, 0.8);
+pub const STUN_TINT_COLOR: Color = Color::srgba(1.0, 1.0, 0.2, 0.5);
+
+// Sound effects playing at once, see `audio`.
+pub const MAX_SFX_VOICES: usize = 16;
+pub const MUSIC_FADE_SECS: f32 = 2.0;
 
 // x coordinates
 pub const RIGHT: f32 = VIEWPORT_WIDTH / 2.;
@@ -23,9 +157,24 @@ pub const LEFT: f32 = -RIGHT;
 pub const TOP: f32 = VIEWPORT_HEIGHT / 2.;
 pub const BOTTOM: f32 = -TOP;
 
+// Number of kills until the next stage begins.
+pub const STAGE_KILLS: usize = 10;
+
+// Background crossfade between two stages, scrolling speeds up to a warp at the middle of it.
+pub const BACKGROUND_TRANSITION_SECS: f32 = 2.5;
+pub const BACKGROUND_WARP_FACTOR: f32 = 8.;
+
 pub const SCOREBOARD_FONT_SIZE: f32 = 33.0;
 pub const SCOREBOARD_TEXT_PADDING: Val = Val::Px(5.0);
 
-pub const BRICK_COLOR: Color = Color::srgb(0.5, 0.5, 1.0);
+pub const OPTIONS_BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.05, 0.8);
+pub const OPTIONS_BUTTON_COLOR: Color = Color::srgb(0.1, 0.1, 0.25);
+pub const OPTIONS_BUTTON_HOVERED_COLOR: Color = Color::srgb(0.2, 0.2, 0.4);
+pub const OPTIONS_BUTTON_PRESSED_COLOR: Color = Color::srgb(0.3, 0.3, 0.6);
+pub const OPTIONS_BUTTON_WIDTH: Val = Val::Px(300.0);
+pub const OPTIONS_BUTTON_GAP: Val = Val::Px(8.0);
+pub const OPTIONS_COLUMNS: u16 = 3;
+pub const OPTIONS_FONT_SIZE: f32 = 18.0;
+
 pub const TEXT_COLOR: Color = Color::srgb(0.5, 0.5, 1.0);
 pub const SCORE_COLOR: Color = Color::srgb(1.0, 0.5, 0.5);
@@ -0,0 +1,126 @@
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+
+use crate::graphics::effects::EffectMaterial;
+use crate::prelude::constants::*;
+use crate::prelude::*;
+use crate::world::pickup::PickupKind;
+
+pub const MESH_COUNT: DiagnosticPath = DiagnosticPath::const_new("assets/meshes");
+pub const COLOR_MATERIAL_COUNT: DiagnosticPath =
+    DiagnosticPath::const_new("assets/color_materials");
+/// Only grows if more ships and wrecks are around than `EffectMaterialPool` holds.
+pub const EFFECT_MATERIAL_COUNT: DiagnosticPath =
+    DiagnosticPath::const_new("assets/effect_materials");
+
+/// Mesh and material handles shared by all spawn sites.
+/// Spawning must only clone these handles, never add new assets, see `warn_on_asset_growth`.
+/// `EffectMaterial`s hold per entity parameters, they are handed out and reused by `EffectMaterials`.
+#[derive(Resource)]
+pub struct GameAssets {
+    /// Quads the sprite sheets of ships are drawn on
+    pub player_mesh: Handle<Mesh>,
+    pub enemy_mesh: Handle<Mesh>,
+    pub projectile_mesh: Handle<Mesh>,
+    pub projectile_material: Handle<ColorMaterial>,
+    pub enemy_projectile_material: Handle<ColorMaterial>,
+    pub laser_impact_mesh: Handle<Mesh>,
+    pub laser_impact_material: Handle<ColorMaterial>,
+    pub pickup_mesh: Handle<Mesh>,
+    pub pickup_materials: Vec<(PickupKind, Handle<ColorMaterial>)>,
+    /// Ring of radius 1, scaled to the size of shockwaves and shields
+    pub ring_mesh: Handle<Mesh>,
+    pub shockwave_material: Handle<ColorMaterial>,
+    pub shield_material: Handle<ColorMaterial>,
+}
+
+impl FromWorld for GameAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let player_mesh = meshes.add(Rectangle::from_length(PLAYER_SIZE));
+        // the collider extends `scale` in each direction, see `physics::collider_aabb`
+        let enemy_mesh = meshes.add(Rectangle::from_length(2.));
+        let projectile_mesh = meshes.add(Ellipse::new(5.0, 10.0));
+        let laser_impact_mesh = meshes.add(Circle::new(12.));
+        let pickup_mesh = meshes.add(Circle::new(1.));
+        let ring_mesh = meshes.add(Annulus::new(0.9, 1.));
+
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        GameAssets {
+            player_mesh,
+            enemy_mesh,
+            projectile_mesh,
+            projectile_material: materials.add(PROJECTILE_COLOR),
+            enemy_projectile_material: materials.add(ENEMY_PROJECTILE_COLOR),
+            laser_impact_mesh,
+            laser_impact_material: materials.add(LASER_IMPACT_COLOR),
+            pickup_mesh,
+            pickup_materials: PickupKind::ALL
+                .iter()
+                .map(|kind| (*kind, materials.add(kind.color())))
+                .collect(),
+            ring_mesh,
+            shockwave_material: materials.add(SHOCKWAVE_COLOR),
+            shield_material: materials.add(SHIELD_COLOR.with_alpha(0.5)),
+        }
+    }
+}
+
+impl GameAssets {
+    pub fn pickup_material(&self, kind: PickupKind) -> Handle<ColorMaterial> {
+        self.pickup_materials
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, handle)| handle.clone())
+            .expect("every pickup has a material")
+    }
+}
+
+pub struct GameAssetsPlugin;
+
+impl Plugin for GameAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameAssets>()
+            .register_diagnostic(Diagnostic::new(MESH_COUNT))
+            .register_diagnostic(Diagnostic::new(COLOR_MATERIAL_COUNT))
+            .register_diagnostic(Diagnostic::new(EFFECT_MATERIAL_COUNT))
+            .add_systems(Update, (measure_asset_counts, warn_on_asset_growth));
+    }
+}
+
+fn measure_asset_counts(
+    mut diagnostics: Diagnostics,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<ColorMaterial>>,
+    effect_materials: Res<Assets<EffectMaterial>>,
+) {
+    diagnostics.add_measurement(&MESH_COUNT, || meshes.len() as f64);
+    diagnostics.add_measurement(&COLOR_MATERIAL_COUNT, || materials.len() as f64);
+    diagnostics.add_measurement(&EFFECT_MATERIAL_COUNT, || effect_materials.len() as f64);
+}
+
+/// Everything is allocated at startup, so the number of meshes and materials must not grow during play.
+fn warn_on_asset_growth(
+    mut highest: Local<Option<(usize, usize, usize)>>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<ColorMaterial>>,
+    effect_materials: Res<Assets<EffectMaterial>>,
+) {
+    let counts = (meshes.len(), materials.len(), effect_materials.len());
+    match *highest {
+        None => *highest = Some(counts),
+        Some(previous)
+            if counts.0 > previous.0 || counts.1 > previous.1 || counts.2 > previous.2 =>
+        {
+            warn!(
+                "Asset count grew during play: {} -> {} meshes, {} -> {} color materials, {} -> {} effect materials",
+                previous.0, counts.0, previous.1, counts.1, previous.2, counts.2
+            );
+            *highest = Some((
+                counts.0.max(previous.0),
+                counts.1.max(previous.1),
+                counts.2.max(previous.2),
+            ));
+        }
+        Some(_) => {}
+    }
+}
@@ -1,67 +1,61 @@
 use crate::prelude::constants::*;
 use crate::prelude::*;
-use bevy::{render::camera::ScalingMode, sprite::Anchor};
+use animation::AnimationPlugin;
+use background::BackgroundPlugin;
+use bevy::render::camera::ScalingMode;
+use camera::{CameraEffectsPlugin, MainCamera};
+use display::DisplayPlugin;
+use effects::EffectsPlugin;
+use particles::ParticlePlugin;
+use post_process::PostProcessPlugin;
 
-#[derive(Component)]
-struct Background;
+pub mod animation;
+pub mod background;
+pub mod camera;
+pub mod display;
+pub mod effects;
+pub mod particles;
+pub mod post_process;
+// the size checks generated by `ShaderType` count as dead code, as the crate does not export them
+#[allow(dead_code)]
+mod uniforms;
 
 pub struct GraphicsPlugin;
 
 impl Plugin for GraphicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (setup,))
-            .add_systems(Update, (move_background,));
+        app.add_plugins((
+            AnimationPlugin,
+            BackgroundPlugin,
+            CameraEffectsPlugin,
+            DisplayPlugin,
+            EffectsPlugin,
+            ParticlePlugin,
+            PostProcessPlugin,
+        ))
+        .add_systems(Startup, (setup,));
     }
 }
 
 // Add the game's entities to our world
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup(mut commands: Commands) {
     // Camera
     commands.spawn((
         Camera2d,
-        Projection::from(OrthographicProjection {
+        // HDR lets bright colors glow with bloom
+        Camera {
+            hdr: true,
+            ..default()
+        },
+        MainCamera,
+        IsDefaultUiCamera,
+        // replaces the projection required by `Camera2d`, zoom pulses change its scale
+        OrthographicProjection {
             scaling_mode: ScalingMode::Fixed {
                 width: VIEWPORT_WIDTH,
                 height: VIEWPORT_HEIGHT,
             },
             ..OrthographicProjection::default_2d()
-        }),
-    ));
-
-    // Background
-    commands.spawn((
-        Background,
-        Sprite {
-            image: asset_server.load("backgrounds/Starfields/Starfield_01-1024x1024.png"),
-            custom_size: Some(Vec2::new(VIEWPORT_WIDTH, VIEWPORT_WIDTH)),
-            anchor: Anchor::BottomCenter,
-            ..default()
-        },
-        Transform {
-            translation: Vec3::new(0.0, BOTTOM - 10.0, -1.0),
-            ..default()
         },
     ));
-    commands.spawn((
-        Background,
-        Sprite {
-            image: asset_server.load("backgrounds/Starfields/Starfield_01-1024x1024.png"),
-            custom_size: Some(Vec2::new(VIEWPORT_WIDTH, VIEWPORT_WIDTH)),
-            anchor: Anchor::BottomCenter,
-            ..default()
-        },
-        Transform {
-            translation: Vec3::new(0.0, TOP, -1.0),
-            ..default()
-        },
-    ));
-}
-
-fn move_background(mut bg_query: Query<&mut Transform, With<Background>>) {
-    for mut transform in &mut bg_query {
-        transform.translation.y -= 5.0;
-        if transform.translation.y < BOTTOM - VIEWPORT_WIDTH {
-            transform.translation.y = TOP;
-        }
-    }
 }
@@ -0,0 +1,350 @@
+/// Sprite sheet animations. Sheets and their clips are plain data, see assets/sprites/sheets.ron.
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use serde::Deserialize;
+
+use crate::prelude::constants::*;
+use crate::prelude::physics::*;
+use crate::prelude::*;
+use crate::world::player::Player;
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AnimationState {
+    Idle,
+    BankLeft,
+    BankRight,
+    Firing,
+    Hurt,
+    Dying,
+}
+
+/// A range of frames of a sprite sheet.
+#[derive(Deserialize, Clone, Debug)]
+pub struct AnimationClip {
+    pub state: AnimationState,
+    pub first: usize,
+    pub last: usize,
+    /// Frames per second
+    pub fps: f32,
+    /// State to continue with after the last frame, the clip loops if it is the clip's own state.
+    /// `None` stops at the last frame and marks the animation as finished.
+    pub next: Option<AnimationState>,
+}
+
+/// An image of equally sized frames in a single row and the clips it contains.
+#[derive(Deserialize, Debug)]
+pub struct SpriteSheet {
+    pub image: String,
+    pub frame_size: UVec2,
+    pub frames: u32,
+    pub clips: Vec<AnimationClip>,
+}
+
+impl SpriteSheet {
+    /// The clip for `state`, falls back to the first clip if the sheet has none.
+    pub fn clip(&self, state: AnimationState) -> &AnimationClip {
+        self.clips
+            .iter()
+            .find(|clip| clip.state == state)
+            .unwrap_or(&self.clips[0])
+    }
+}
+
+/// The sprite sheets of the game, defined in assets/sprites/sheets.ron.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Sheet {
+    Player,
+    Creep,
+    Standard,
+    Explosion,
+}
+
+impl Sheet {
+    pub const ALL: [Sheet; 4] = [
+        Sheet::Player,
+        Sheet::Creep,
+        Sheet::Standard,
+        Sheet::Explosion,
+    ];
+
+    pub fn get(self) -> &'static SpriteSheet {
+        &SHEETS[&self]
+    }
+}
+
+/// Parsed once on first use, the file is embedded like the sound effect parameters.
+static SHEETS: LazyLock<HashMap<Sheet, SpriteSheet>> = LazyLock::new(|| {
+    ron::from_str(include_str!("../../assets/sprites/sheets.ron")).expect("sprite sheets are valid")
+});
+
+/// Image and atlas layout handles of every sprite sheet, loaded at startup.
+#[derive(Resource)]
+pub struct SpriteSheets {
+    handles: HashMap<Sheet, (Handle<Image>, Handle<TextureAtlasLayout>)>,
+}
+
+impl FromWorld for SpriteSheets {
+    fn from_world(world: &mut World) -> Self {
+        let images: Vec<_> = {
+            let asset_server = world.resource::<AssetServer>();
+            Sheet::ALL
+                .iter()
+                .map(|sheet| asset_server.load(sheet.get().image.as_str()))
+                .collect()
+        };
+
+        let mut layouts = world.resource_mut::<Assets<TextureAtlasLayout>>();
+        let handles = Sheet::ALL
+            .into_iter()
+            .zip(images)
+            .map(|(sheet, image)| {
+                let SpriteSheet {
+                    frame_size, frames, ..
+                } = sheet.get();
+                let layout = TextureAtlasLayout::from_grid(*frame_size, *frames, 1, None, None);
+                (sheet, (image, layouts.add(layout)))
+            })
+            .collect();
+
+        SpriteSheets { handles }
+    }
+}
+
+impl SpriteSheets {
+    pub fn image(&self, sheet: Sheet) -> Handle<Image> {
+        self.handles[&sheet].0.clone()
+    }
+
+    /// A sprite showing the first frame of `sheet`, drawn with the given size.
+    pub fn sprite(&self, sheet: Sheet, size: Vec2) -> Sprite {
+        let (image, layout) = &self.handles[&sheet];
+        Sprite {
+            custom_size: Some(size),
+            ..Sprite::from_atlas_image(
+                image.clone(),
+                TextureAtlas {
+                    layout: layout.clone(),
+                    index: 0,
+                },
+            )
+        }
+    }
+}
+
+/// Plays the clips of a sprite sheet on the atlas of the entity's `Sprite`,
+/// or on its `EffectMaterial` if it has no sprite.
+#[derive(Component, Debug)]
+pub struct SpriteAnimation {
+    sheet: Sheet,
+    state: AnimationState,
+    frame: usize,
+    elapsed: f32,
+    finished: bool,
+}
+
+impl SpriteAnimation {
+    pub fn new(sheet: Sheet, state: AnimationState) -> Self {
+        SpriteAnimation {
+            sheet,
+            state,
+            frame: sheet.get().clip(state).first,
+            elapsed: 0.,
+            finished: false,
+        }
+    }
+
+    pub fn sheet(&self) -> Sheet {
+        self.sheet
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Switches to the clip of `state`, restarting it only if it is not already playing.
+    pub fn play(&mut self, state: AnimationState) {
+        if self.state != state {
+            self.restart(state);
+        }
+    }
+
+    /// Switches to the clip of `state` and starts it from its first frame.
+    pub fn restart(&mut self, state: AnimationState) {
+        *self = SpriteAnimation::new(self.sheet, state);
+    }
+
+    /// Advances the animation by `dt` seconds.
+    pub fn tick(&mut self, dt: f32) {
+        if self.finished {
+            return;
+        }
+        self.elapsed += dt;
+
+        let clip = self.sheet.get().clip(self.state);
+        let frame_time = 1. / clip.fps;
+        while self.elapsed >= frame_time {
+            self.elapsed -= frame_time;
+            if self.frame < clip.last {
+                self.frame += 1;
+                continue;
+            }
+            match clip.next {
+                Some(next) if next == self.state => self.frame = clip.first,
+                Some(next) => {
+                    self.restart(next);
+                    return;
+                }
+                None => {
+                    self.finished = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Despawns the entity once its animation is finished, e.g. explosions and wrecks.
+#[derive(Component)]
+pub struct DespawnOnFinish;
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpriteSheets>().add_systems(
+            Update,
+            (
+                (
+                    on_enemy_hit_hurt,
+                    on_enemy_fired_firing,
+                    on_enemy_died_explosion,
+                    bank_player,
+                ),
+                animate,
+                despawn_finished,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn on_enemy_hit_hurt(
+    mut events: EventReader<EnemyHitEvent>,
+    mut query: Query<&mut SpriteAnimation, With<Enemy>>,
+) {
+    for event in events.read() {
+        if let Ok(mut animation) = query.get_mut(event.entity) {
+            animation.restart(AnimationState::Hurt);
+        }
+    }
+}
+
+fn on_enemy_fired_firing(
+    mut events: EventReader<EnemyFiredEvent>,
+    mut query: Query<&mut SpriteAnimation, With<Enemy>>,
+) {
+    for event in events.read() {
+        if let Ok(mut animation) = query.get_mut(event.entity) {
+            animation.restart(AnimationState::Firing);
+        }
+    }
+}
+
+/// Banks the ship into the direction it moves horizontally.
+fn bank_player(query: Single<(&Velocity, &mut SpriteAnimation), With<Player>>) {
+    let (velocity, mut animation) = query.into_inner();
+    let state = if velocity.x < -PLAYER_BANK_SPEED {
+        AnimationState::BankLeft
+    } else if velocity.x > PLAYER_BANK_SPEED {
+        AnimationState::BankRight
+    } else {
+        AnimationState::Idle
+    };
+    animation.play(state);
+}
+
+fn animate(mut query: Query<(&mut SpriteAnimation, Option<&mut Sprite>)>, time: Res<Time>) {
+    for (mut animation, sprite) in &mut query {
+        animation.tick(time.delta_secs());
+        if let Some(mut sprite) = sprite
+            && let Some(atlas) = sprite.texture_atlas.as_mut()
+            && atlas.index != animation.frame()
+        {
+            atlas.index = animation.frame();
+        }
+    }
+}
+
+fn despawn_finished(
+    mut commands: Commands,
+    query: Query<(Entity, &SpriteAnimation), With<DespawnOnFinish>>,
+) {
+    for (entity, animation) in &query {
+        if animation.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn on_enemy_died_explosion(
+    mut commands: Commands,
+    sheets: Res<SpriteSheets>,
+    mut events: EventReader<EnemyDiedEvent>,
+) {
+    for event in events.read() {
+        commands.spawn((
+            Name::new("Explosion"),
+            sheets.sprite(Sheet::Explosion, Vec2::splat(EXPLOSION_SIZE)),
+            Transform::from_translation(event.position.extend(1.)),
+            SpriteAnimation::new(Sheet::Explosion, AnimationState::Idle),
+            DespawnOnFinish,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sheets_are_valid() {
+        for sheet in Sheet::ALL.map(Sheet::get) {
+            for clip in &sheet.clips {
+                assert!(clip.first <= clip.last && clip.last < sheet.frames as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn firing_returns_to_idle() {
+        let mut animation = SpriteAnimation::new(Sheet::Standard, AnimationState::Idle);
+        animation.restart(AnimationState::Firing);
+        assert_eq!(animation.frame(), 4);
+        animation.tick(1.);
+        assert_eq!(animation.state, AnimationState::Idle);
+    }
+
+    #[test]
+    fn hurt_returns_to_idle() {
+        let mut animation = SpriteAnimation::new(Sheet::Creep, AnimationState::Hurt);
+        animation.tick(1.);
+        assert_eq!(animation.state, AnimationState::Idle);
+        assert!(!animation.is_finished());
+    }
+
+    #[test]
+    fn dying_finishes_on_last_frame() {
+        let mut animation = SpriteAnimation::new(Sheet::Creep, AnimationState::Dying);
+        animation.tick(10.);
+        assert!(animation.is_finished());
+        assert_eq!(
+            animation.frame(),
+            Sheet::Creep.get().clip(AnimationState::Dying).last
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use std::f32::consts::PI;
+
+use bevy::ecs::component::ComponentId;
+use bevy::ecs::world::DeferredWorld;
+use bevy::sprite::Anchor;
+
+use crate::prelude::constants::*;
+use crate::prelude::*;
+use crate::world::stage::StageChangedEvent;
+
+/// The images used for the background layers of a stage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BackgroundTheme {
+    pub starfield: &'static str,
+    pub nebula: &'static str,
+    pub dust: &'static str,
+}
+
+pub const THEMES: [BackgroundTheme; 3] = [
+    BackgroundTheme {
+        starfield: "backgrounds/Starfields/Starfield_01-1024x1024.png",
+        nebula: "backgrounds/Blue Nebula/Blue_Nebula_01-1024x1024.png",
+        dust: "backgrounds/Starfields/Starfield_05-1024x1024.png",
+    },
+    BackgroundTheme {
+        starfield: "backgrounds/Starfields/Starfield_02-1024x1024.png",
+        nebula: "backgrounds/Green Nebula/Green_Nebula_03-1024x1024.png",
+        dust: "backgrounds/Starfields/Starfield_06-1024x1024.png",
+    },
+    BackgroundTheme {
+        starfield: "backgrounds/Starfields/Starfield_07-1024x1024.png",
+        nebula: "backgrounds/Purple Nebula/Purple_Nebula_05-1024x1024.png",
+        dust: "backgrounds/Starfields/Starfield_03-1024x1024.png",
+    },
+];
+
+impl BackgroundTheme {
+    /// Stages cycle through the available themes.
+    pub fn for_stage(stage: usize) -> BackgroundTheme {
+        THEMES[stage % THEMES.len()]
+    }
+
+    fn image(&self, depth: Depth) -> &'static str {
+        match depth {
+            Depth::Far => self.starfield,
+            Depth::Mid => self.nebula,
+            Depth::Near => self.dust,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Depth {
+    Far,
+    Mid,
+    Near,
+}
+
+struct LayerSpec {
+    depth: Depth,
+    /// Scroll speed in units per second
+    speed: f32,
+    z: f32,
+    alpha: f32,
+}
+
+/// Layers further away scroll slower, which gives the illusion of depth.
+const LAYERS: [LayerSpec; 3] = [
+    LayerSpec {
+        depth: Depth::Far,
+        speed: 30.,
+        z: -3.,
+        alpha: 1.,
+    },
+    LayerSpec {
+        depth: Depth::Mid,
+        speed: 80.,
+        z: -2.,
+        alpha: 0.6,
+    },
+    LayerSpec {
+        depth: Depth::Near,
+        speed: 220.,
+        z: -1.,
+        alpha: 0.4,
+    },
+];
+
+/// Side length of a background tile, each layer consists of two tiles stacked on top of each other.
+const TILE_SIZE: f32 = VIEWPORT_WIDTH;
+
+#[derive(Component)]
+pub struct Background;
+
+/// A tile of a scrolling background layer.
+#[derive(Component)]
+pub struct ParallaxLayer {
+    speed: f32,
+    alpha: f32,
+}
+
+/// Fades a tile in or out during a theme transition, tiles are despawned once faded out.
+/// Tiles fading in are drawn slightly in front, so they blend over the old ones.
+#[derive(Component)]
+#[component(on_insert = on_insert_fade, on_replace = on_replace_fade)]
+struct Fade {
+    fade_in: bool,
+    /// Opacity at the start, a tile may still be fading when the next transition starts
+    from: f32,
+}
+
+impl Fade {
+    fn opacity(&self, t: f32) -> f32 {
+        let to = if self.fade_in { 1. } else { 0. };
+        self.from + (to - self.from) * t
+    }
+}
+
+const FADE_IN_Z_OFFSET: f32 = 0.5;
+
+fn on_insert_fade(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    if world.get::<Fade>(entity).is_some_and(|fade| fade.fade_in)
+        && let Some(mut transform) = world.get_mut::<Transform>(entity)
+    {
+        transform.translation.z += FADE_IN_Z_OFFSET;
+    }
+}
+
+/// Also runs when a fade is replaced by another one.
+fn on_replace_fade(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+    if world.get::<Fade>(entity).is_some_and(|fade| fade.fade_in)
+        && let Some(mut transform) = world.get_mut::<Transform>(entity)
+    {
+        transform.translation.z -= FADE_IN_Z_OFFSET;
+    }
+}
+
+/// A running transition between two themes.
+#[derive(Resource, Default)]
+pub struct BackgroundTransition {
+    elapsed: Option<f32>,
+}
+
+impl BackgroundTransition {
+    /// Progress of the transition in `0..=1`, `None` if there is no transition.
+    fn progress(&self) -> Option<f32> {
+        self.elapsed
+            .map(|elapsed| (elapsed / BACKGROUND_TRANSITION_SECS).min(1.))
+    }
+
+    /// Scroll speed multiplier, ramps up to the warp factor and back down during a transition.
+    fn speed_factor(&self) -> f32 {
+        match self.progress() {
+            Some(t) => 1. + (BACKGROUND_WARP_FACTOR - 1.) * (t * PI).sin(),
+            None => 1.,
+        }
+    }
+}
+
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BackgroundTransition>()
+            .add_systems(Startup, (setup,))
+            .add_systems(Update, (on_stage_changed, fade, scroll).chain());
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_layers(
+        &mut commands,
+        &asset_server,
+        BackgroundTheme::for_stage(0),
+        1.,
+    );
+}
+
+/// Spawns all layers of a theme, `opacity` scales the alpha of every layer.
+fn spawn_layers(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    theme: BackgroundTheme,
+    opacity: f32,
+) -> Vec<Entity> {
+    let mut tiles = vec![];
+    for layer in &LAYERS {
+        let image = asset_server.load(theme.image(layer.depth));
+        for i in 0..2 {
+            let tile = commands.spawn((
+                Name::new("Background"),
+                Background,
+                ParallaxLayer {
+                    speed: layer.speed,
+                    alpha: layer.alpha,
+                },
+                Sprite {
+                    image: image.clone(),
+                    color: Color::WHITE.with_alpha(layer.alpha * opacity),
+                    custom_size: Some(Vec2::splat(TILE_SIZE)),
+                    anchor: Anchor::BottomCenter,
+                    ..default()
+                },
+                Transform::from_xyz(0.0, BOTTOM + i as f32 * TILE_SIZE, layer.z),
+            ));
+            tiles.push(tile.id());
+        }
+    }
+    tiles
+}
+
+/// Crossfades to the theme of the new stage: the current tiles fade out, the new ones in.
+fn on_stage_changed(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut ev_stage_changed: EventReader<StageChangedEvent>,
+    mut transition: ResMut<BackgroundTransition>,
+    tiles: Query<(Entity, &Sprite, &ParallaxLayer)>,
+) {
+    let Some(event) = ev_stage_changed.read().last() else {
+        return;
+    };
+
+    for (tile, sprite, layer) in &tiles {
+        commands.entity(tile).insert(Fade {
+            fade_in: false,
+            from: sprite.color.alpha() / layer.alpha,
+        });
+    }
+
+    let theme = BackgroundTheme::for_stage(event.stage);
+    for tile in spawn_layers(&mut commands, &asset_server, theme, 0.) {
+        commands.entity(tile).insert(Fade {
+            fade_in: true,
+            from: 0.,
+        });
+    }
+
+    transition.elapsed = Some(0.);
+}
+
+fn fade(
+    mut commands: Commands,
+    mut transition: ResMut<BackgroundTransition>,
+    mut query: Query<(Entity, &mut Sprite, &ParallaxLayer, &Fade)>,
+    time: Res<Time>,
+) {
+    let Some(elapsed) = transition.elapsed.as_mut() else {
+        return;
+    };
+    *elapsed += time.delta_secs();
+
+    let t = transition.progress().unwrap_or(1.);
+    for (entity, mut sprite, layer, fade) in &mut query {
+        sprite.color.set_alpha(layer.alpha * fade.opacity(t));
+
+        if t >= 1. {
+            if fade.fade_in {
+                commands.entity(entity).remove::<Fade>();
+            } else {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    if t >= 1. {
+        transition.elapsed = None;
+    }
+}
+
+fn scroll(
+    mut query: Query<(&mut Transform, &ParallaxLayer)>,
+    transition: Res<BackgroundTransition>,
+    time: Res<Time>,
+) {
+    let factor = transition.speed_factor();
+    for (mut transform, layer) in &mut query {
+        transform.translation.y -= layer.speed * factor * time.delta_secs();
+        // once a tile left the screen it is put on top of the other one
+        if transform.translation.y <= BOTTOM - TILE_SIZE {
+            transform.translation.y += 2. * TILE_SIZE;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fades_can_be_replaced() {
+        let mut world = World::new();
+        let tile = world
+            .spawn((
+                Transform::default(),
+                Fade {
+                    fade_in: true,
+                    from: 0.,
+                },
+            ))
+            .id();
+        let z = |world: &World| world.get::<Transform>(tile).unwrap().translation.z;
+        assert_eq!(z(&world), FADE_IN_Z_OFFSET);
+
+        // the next transition starts halfway through fading in
+        let fade_out = Fade {
+            fade_in: false,
+            from: Fade {
+                fade_in: true,
+                from: 0.,
+            }
+            .opacity(0.5),
+        };
+        assert_eq!(fade_out.opacity(0.), 0.5);
+        world.entity_mut(tile).insert(fade_out);
+        assert_eq!(z(&world), 0.);
+        world.entity_mut(tile).remove::<Fade>();
+        assert_eq!(z(&world), 0.);
+    }
+}
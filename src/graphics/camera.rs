@@ -0,0 +1,212 @@
+/// Camera effects: trauma based screen shake, hit-stop/slow motion and zoom pulses.
+use crate::prelude::constants::*;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+use crate::settings::Settings;
+use crate::world::bomb::BombDetonatedEvent;
+use crate::world::player::PlayerHitByEnemyEvent;
+
+/// The camera all effects are applied to.
+#[derive(Component)]
+pub struct MainCamera;
+
+/// Every effect can be toggled or scaled, e.g. for players sensitive to motion.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct CameraSettings {
+    /// Multiplier of the shake, 0 disables it
+    pub shake: f32,
+    pub hit_stop: bool,
+    pub zoom_pulses: bool,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        CameraSettings {
+            shake: 1.,
+            hit_stop: true,
+            zoom_pulses: true,
+        }
+    }
+}
+
+/// Running camera effects. Trigger them through this resource, e.g. on explosions or boss attacks.
+/// Effects run on real time, so they continue during a hit-stop.
+#[derive(Resource, Default, Debug)]
+pub struct CameraEffects {
+    /// Shake strength in `0..=1`, the shake grows with the square of it
+    trauma: f32,
+    /// Game speed and remaining real seconds of a slow motion
+    slow_motion: Option<(f32, f32)>,
+    /// Current zoom in, subtracted from the projection scale
+    zoom: f32,
+    /// Intensity of the chromatic aberration, see `post_process`
+    aberration: f32,
+}
+
+impl CameraEffects {
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).min(1.);
+    }
+
+    /// Runs the game at `speed` for `secs` real seconds, a very low speed freezes it for a hit-stop.
+    pub fn slow_motion(&mut self, speed: f32, secs: f32) {
+        self.slow_motion = Some((speed, secs));
+    }
+
+    pub fn hit_stop(&mut self, secs: f32) {
+        self.slow_motion(HIT_STOP_SPEED, secs);
+    }
+
+    /// Briefly zooms in by `amount`, e.g. 0.05 zooms in by 5%.
+    pub fn zoom_pulse(&mut self, amount: f32) {
+        self.zoom = self.zoom.max(amount);
+    }
+
+    /// Briefly splits the colors at the edges of the screen.
+    pub fn aberration_pulse(&mut self, intensity: f32) {
+        self.aberration = self.aberration.max(intensity);
+    }
+
+    pub fn aberration(&self) -> f32 {
+        self.aberration
+    }
+
+    /// Offset and roll of the camera, `t` is the elapsed time and moves the shake along.
+    fn shake(&self, t: f32) -> (Vec2, f32) {
+        let shake = self.trauma * self.trauma;
+        // overlapping sines give a smooth but irregular movement
+        let noise = |seed: f32| {
+            ((t * SHAKE_FREQUENCY + seed).sin() + (t * SHAKE_FREQUENCY * 2.3 + seed * 1.7).sin())
+                / 2.
+        };
+        let offset = Vec2::new(noise(0.), noise(10.)) * SHAKE_MAX_OFFSET * shake;
+        (offset, noise(20.) * SHAKE_MAX_ROLL * shake)
+    }
+
+    fn tick(&mut self, dt: f32) {
+        self.trauma = (self.trauma - SHAKE_DECAY * dt).max(0.);
+        self.zoom = (self.zoom - ZOOM_PULSE_DECAY * dt).max(0.);
+        self.aberration = (self.aberration - ABERRATION_DECAY * dt).max(0.);
+        if let Some((_, remaining)) = self.slow_motion.as_mut() {
+            *remaining -= dt;
+            if *remaining <= 0. {
+                self.slow_motion = None;
+            }
+        }
+    }
+}
+
+pub struct CameraEffectsPlugin;
+
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraEffects>().add_systems(
+            Update,
+            (
+                (on_enemy_died_shake, on_player_hit_shake, on_bomb_shake),
+                tick,
+                (apply_shake, apply_zoom, apply_slow_motion),
+            )
+                .chain(),
+        );
+    }
+}
+
+fn on_enemy_died_shake(
+    mut effects: ResMut<CameraEffects>,
+    mut events: EventReader<EnemyDiedEvent>,
+) {
+    for event in events.read() {
+        // overkills shake harder
+        effects.add_trauma(ENEMY_DIED_TRAUMA + event.overkill * OVERKILL_TRAUMA);
+        effects.zoom_pulse(ENEMY_DIED_ZOOM);
+    }
+}
+
+fn on_player_hit_shake(
+    mut effects: ResMut<CameraEffects>,
+    mut events: EventReader<PlayerHitByEnemyEvent>,
+) {
+    for _ in events.read() {
+        effects.add_trauma(PLAYER_HIT_TRAUMA);
+        effects.hit_stop(PLAYER_HIT_STOP_SECS);
+        effects.aberration_pulse(PLAYER_HIT_ABERRATION);
+    }
+}
+
+fn on_bomb_shake(mut effects: ResMut<CameraEffects>, mut events: EventReader<BombDetonatedEvent>) {
+    for _ in events.read() {
+        effects.add_trauma(BOMB_TRAUMA);
+        effects.zoom_pulse(BOMB_ZOOM);
+        effects.aberration_pulse(BOMB_ABERRATION);
+    }
+}
+
+fn tick(mut effects: ResMut<CameraEffects>, time: Res<Time<Real>>) {
+    effects.tick(time.delta_secs());
+}
+
+/// The camera rests at the origin, the shake is its whole offset.
+fn apply_shake(
+    effects: Res<CameraEffects>,
+    settings: Res<Settings>,
+    camera: Single<&mut Transform, With<MainCamera>>,
+    time: Res<Time<Real>>,
+) {
+    let (offset, roll) = effects.shake(time.elapsed_secs());
+    let mut transform = camera.into_inner();
+    transform.translation = (offset * settings.camera.shake).extend(transform.translation.z);
+    transform.rotation = Quat::from_rotation_z(roll * settings.camera.shake);
+}
+
+fn apply_zoom(
+    effects: Res<CameraEffects>,
+    settings: Res<Settings>,
+    camera: Single<&mut OrthographicProjection, With<MainCamera>>,
+) {
+    let zoom = if settings.camera.zoom_pulses {
+        effects.zoom
+    } else {
+        0.
+    };
+    let mut projection = camera.into_inner();
+    projection.scale = 1. - zoom;
+}
+
+fn apply_slow_motion(
+    effects: Res<CameraEffects>,
+    settings: Res<Settings>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let speed = match effects.slow_motion {
+        Some((speed, _)) if settings.camera.hit_stop => speed,
+        _ => 1.,
+    };
+    if time.relative_speed() != speed {
+        time.set_relative_speed(speed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effects_calm_down() {
+        let mut effects = CameraEffects::default();
+        effects.add_trauma(0.8);
+        effects.add_trauma(0.8);
+        effects.zoom_pulse(0.1);
+        effects.hit_stop(0.1);
+        assert_eq!(effects.trauma, 1.);
+
+        effects.aberration_pulse(0.1);
+        effects.tick(10.);
+        assert_eq!(effects.shake(1.), (Vec2::ZERO, 0.));
+        assert_eq!(effects.aberration(), 0.);
+        assert_eq!(effects.zoom, 0.);
+        assert!(effects.slow_motion.is_none());
+    }
+}
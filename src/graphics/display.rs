@@ -0,0 +1,215 @@
+/// Window resolution, window mode, vsync and how the playfield is scaled to the window.
+use bevy::render::camera::Viewport;
+use bevy::render::view::RenderLayers;
+use bevy::window::{MonitorSelection, PresentMode, PrimaryWindow, WindowMode, WindowResolution};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::constants::*;
+use crate::prelude::*;
+use crate::settings::Settings;
+
+use super::camera::MainCamera;
+
+/// Window resolutions selectable in the options menu, in logical pixels.
+pub const RESOLUTIONS: [UVec2; 5] = [
+    UVec2::new(960, 540),
+    UVec2::new(1280, 720),
+    UVec2::new(1600, 900),
+    UVec2::new(1920, 1080),
+    UVec2::new(2560, 1440),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayMode {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    fn window_mode(&self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            DisplayMode::Fullscreen => WindowMode::Fullscreen(MonitorSelection::Current),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scaling {
+    /// Keeps the aspect ratio of the playfield, with black bars on the sides or top and bottom
+    Letterbox,
+    /// Fills the whole window, distorting the playfield if the aspect ratios differ
+    Stretch,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct DisplaySettings {
+    /// Window size in windowed mode, in logical pixels
+    pub resolution: UVec2,
+    pub mode: DisplayMode,
+    pub vsync: bool,
+    pub scaling: Scaling,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        DisplaySettings {
+            resolution: UVec2::new(VIEWPORT_WIDTH as u32 / 2, VIEWPORT_HEIGHT as u32 / 2),
+            mode: DisplayMode::Windowed,
+            vsync: true,
+            scaling: Scaling::Letterbox,
+        }
+    }
+}
+
+impl DisplaySettings {
+    /// The primary window as configured, see `GamePlugin`.
+    pub fn window(&self) -> Window {
+        Window {
+            resolution: WindowResolution::new(self.resolution.x as f32, self.resolution.y as f32),
+            mode: self.mode.window_mode(),
+            present_mode: self.present_mode(),
+            ..default()
+        }
+    }
+
+    fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+}
+
+/// Largest area of `window_size` with the aspect ratio of the playfield, centered in the window.
+fn letterbox(window_size: UVec2) -> (UVec2, UVec2) {
+    let aspect = VIEWPORT_WIDTH / VIEWPORT_HEIGHT;
+    let window = window_size.as_vec2();
+    let size = if window.x / window.y > aspect {
+        Vec2::new(window.y * aspect, window.y)
+    } else {
+        Vec2::new(window.x, window.x / aspect)
+    }
+    .round()
+    .as_uvec2()
+    .min(window_size);
+    ((window_size - size) / 2, size)
+}
+
+/// Renders nothing, but clears the bars around a letterboxed playfield.
+#[derive(Component)]
+struct LetterboxCamera;
+
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (spawn_letterbox_camera,))
+            .add_systems(
+                Update,
+                (
+                    apply_window_settings.run_if(resource_changed::<Settings>),
+                    update_viewport,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn spawn_letterbox_camera(mut commands: Commands) {
+    commands.spawn((
+        Name::new("LetterboxCamera"),
+        LetterboxCamera,
+        Camera2d,
+        Camera {
+            order: -1,
+            clear_color: ClearColorConfig::Custom(Color::BLACK),
+            ..default()
+        },
+        RenderLayers::layer(LETTERBOX_RENDER_LAYER),
+    ));
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    window: Single<&mut Window, With<PrimaryWindow>>,
+) {
+    let mut window = window.into_inner();
+    let display = &settings.display;
+
+    // fullscreen windows take the size of the monitor
+    let resolution = display.resolution.as_vec2();
+    if display.mode == DisplayMode::Windowed && window.resolution.size() != resolution {
+        window.resolution.set(resolution.x, resolution.y);
+    }
+    if window.mode != display.mode.window_mode() {
+        window.mode = display.mode.window_mode();
+    }
+    if window.present_mode != display.present_mode() {
+        window.present_mode = display.present_mode();
+    }
+}
+
+fn update_viewport(
+    settings: Res<Settings>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<&mut Camera, With<MainCamera>>,
+) {
+    let size = window.physical_size();
+    // minimized windows have no size
+    if size.x == 0 || size.y == 0 {
+        return;
+    }
+
+    let viewport = match settings.display.scaling {
+        Scaling::Letterbox => {
+            let (physical_position, physical_size) = letterbox(size);
+            Some(Viewport {
+                physical_position,
+                physical_size,
+                ..default()
+            })
+        }
+        Scaling::Stretch => None,
+    };
+
+    let mut camera = camera.into_inner();
+    let current = camera
+        .viewport
+        .as_ref()
+        .map(|viewport| (viewport.physical_position, viewport.physical_size));
+    let new = viewport
+        .as_ref()
+        .map(|viewport| (viewport.physical_position, viewport.physical_size));
+    if current != new {
+        camera.viewport = viewport;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letterbox_keeps_aspect_ratio() {
+        // exactly 16:9
+        assert_eq!(
+            letterbox(UVec2::new(1920, 1080)),
+            (UVec2::ZERO, UVec2::new(1920, 1080))
+        );
+        // too wide: pillarbox
+        assert_eq!(
+            letterbox(UVec2::new(2560, 1080)),
+            (UVec2::new(320, 0), UVec2::new(1920, 1080))
+        );
+        // too tall: letterbox
+        assert_eq!(
+            letterbox(UVec2::new(1600, 1200)),
+            (UVec2::new(0, 150), UVec2::new(1600, 900))
+        );
+    }
+}
@@ -0,0 +1,425 @@
+/// Damage feedback: hit flash, tint, dissolve and blink, rendered by `EffectMaterial`.
+use std::sync::Arc;
+
+use bevy::ecs::system::SystemParam;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef};
+use bevy::sprite::{AlphaMode2d, Material2d, Material2dPlugin};
+
+use crate::pool::{PoolCommands, PoolPlugin, Poolable};
+use crate::prelude::constants::*;
+use crate::prelude::*;
+use crate::world::bomb::BombDetonatedEvent;
+use crate::world::player::{Player, PlayerHitByEnemyEvent};
+use crate::world::status::StatusEffects;
+
+use super::animation::{AnimationState, Sheet, SpriteAnimation, SpriteSheets};
+
+/// Draws a frame of a sprite sheet on a quad, with the effects of `MaterialEffects` applied.
+/// Each entity needs its own material, as the parameters differ per entity, see `EffectMaterials`.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct EffectMaterial {
+    #[uniform(0)]
+    pub params: EffectParams,
+    #[texture(1)]
+    #[sampler(2)]
+    pub texture: Handle<Image>,
+}
+
+pub use super::uniforms::EffectParams;
+
+impl EffectMaterial {
+    fn new(texture: Handle<Image>, frames: u32) -> Self {
+        EffectMaterial {
+            params: EffectParams {
+                tint: Vec4::ZERO,
+                flash: 0.,
+                dissolve: 0.,
+                opacity: 1.,
+                frame: 0,
+                frames,
+            },
+            texture,
+        }
+    }
+}
+
+/// All `EffectMaterial`s, allocated at startup like the handles of `GameAssets`.
+#[derive(Resource)]
+pub struct EffectMaterialPool(Vec<Handle<EffectMaterial>>);
+
+impl FromWorld for EffectMaterialPool {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<EffectMaterial>>();
+        EffectMaterialPool(
+            (0..EFFECT_MATERIAL_POOL_SIZE)
+                .map(|_| materials.add(EffectMaterial::new(Handle::default(), 1)))
+                .collect(),
+        )
+    }
+}
+
+/// Hands out the materials of `EffectMaterialPool`. A material is reused once no entity
+/// holds its handle anymore, so new ones are only added if more ships are around than ever before.
+#[derive(SystemParam)]
+pub struct EffectMaterials<'w> {
+    pool: ResMut<'w, EffectMaterialPool>,
+    materials: ResMut<'w, Assets<EffectMaterial>>,
+    sheets: Res<'w, SpriteSheets>,
+}
+
+impl EffectMaterials<'_> {
+    /// A material showing the first frame of `sheet` without any effects.
+    pub fn add(&mut self, sheet: Sheet) -> Handle<EffectMaterial> {
+        let material = EffectMaterial::new(self.sheets.image(sheet), sheet.get().frames);
+        // the pool holds the only handle of a free material
+        let free =
+            self.pool.0.iter().find(
+                |handle| matches!(handle, Handle::Strong(arc) if Arc::strong_count(arc) == 1),
+            );
+        if let Some(handle) = free
+            && let Some(reused) = self.materials.get_mut(handle)
+        {
+            *reused = material;
+            return handle.clone();
+        }
+        let handle = self.materials.add(material);
+        self.pool.0.push(handle.clone());
+        handle
+    }
+}
+
+impl Material2d for EffectMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/effects.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+#[derive(Debug)]
+struct Tint {
+    color: LinearRgba,
+    duration: f32,
+    remaining: f32,
+}
+
+/// Running effects of an entity drawn with an `EffectMaterial`. Durations are in seconds.
+#[derive(Component, Default, Debug)]
+pub struct MaterialEffects {
+    flash: f32,
+    tint: Option<Tint>,
+    /// Constant tint while a status effect lasts, a fading tint is drawn on top of it
+    status_tint: Option<LinearRgba>,
+    dissolve: Option<f32>,
+    blink: f32,
+}
+
+impl MaterialEffects {
+    /// Draws the entity white for `secs`.
+    pub fn flash(&mut self, secs: f32) {
+        self.flash = self.flash.max(secs);
+    }
+
+    /// Tints the entity with `color`, the tint fades out over `secs`.
+    pub fn tint(&mut self, color: Color, secs: f32) {
+        self.tint = Some(Tint {
+            color: color.to_linear(),
+            duration: secs,
+            remaining: secs,
+        });
+    }
+
+    pub fn set_status_tint(&mut self, color: Option<Color>) {
+        self.status_tint = color.map(|color| color.to_linear());
+    }
+
+    /// Starts dissolving the entity, see `DISSOLVE_SECS`.
+    pub fn dissolve(&mut self) {
+        self.dissolve.get_or_insert(0.);
+    }
+
+    pub fn is_dissolved(&self) -> bool {
+        self.dissolve
+            .is_some_and(|elapsed| elapsed >= DISSOLVE_SECS)
+    }
+
+    /// Blinks the entity for `secs`, e.g. while it is invulnerable.
+    pub fn blink(&mut self, secs: f32) {
+        self.blink = self.blink.max(secs);
+    }
+
+    pub fn is_blinking(&self) -> bool {
+        self.blink > 0.
+    }
+
+    fn tick(&mut self, dt: f32) {
+        self.flash = (self.flash - dt).max(0.);
+        self.blink = (self.blink - dt).max(0.);
+        if let Some(tint) = self.tint.as_mut() {
+            tint.remaining -= dt;
+            if tint.remaining <= 0. {
+                self.tint = None;
+            }
+        }
+        if let Some(elapsed) = self.dissolve.as_mut() {
+            *elapsed += dt;
+        }
+    }
+
+    fn apply(&self, params: &mut EffectParams) {
+        params.flash = if self.flash > 0. { 1. } else { 0. };
+        params.tint = match &self.tint {
+            Some(tint) => {
+                let strength = tint.color.alpha * tint.remaining / tint.duration;
+                tint.color.with_alpha(strength).to_vec4()
+            }
+            None => self.status_tint.map_or(Vec4::ZERO, |color| color.to_vec4()),
+        };
+        params.dissolve = self
+            .dissolve
+            .map_or(0., |elapsed| (elapsed / DISSOLVE_SECS).min(1.));
+        // alternate between visible and faint, starting visible
+        params.opacity =
+            if self.is_blinking() && (self.blink * BLINK_FREQUENCY * 2.) as u32 % 2 == 1 {
+                BLINK_OPACITY
+            } else {
+                1.
+            };
+    }
+}
+
+/// Remains of a dead enemy, returned to the pool once dissolved.
+/// A released wreck gives up the material it took over from the enemy.
+#[derive(Component)]
+pub struct Wreck;
+
+impl Poolable for Wreck {
+    fn strip(entity: &mut EntityCommands) {
+        entity.remove::<(
+            Self,
+            SpriteAnimation,
+            MaterialEffects,
+            MeshMaterial2d<EffectMaterial>,
+        )>();
+    }
+}
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            Material2dPlugin::<EffectMaterial>::default(),
+            PoolPlugin::<Wreck>::default(),
+        ))
+        .init_resource::<EffectMaterialPool>()
+        .add_systems(FixedUpdate, spawn_wrecks.in_set(GameSet::Cleanup))
+        .add_systems(
+            Update,
+            (
+                (
+                    on_enemy_hit_flash,
+                    on_player_hit_blink,
+                    on_bomb_blink,
+                    show_status_tints,
+                ),
+                tick,
+                release_dissolved,
+            )
+                .chain(),
+        )
+        .add_systems(PostUpdate, update_materials);
+    }
+}
+
+fn on_enemy_hit_flash(
+    mut events: EventReader<EnemyHitEvent>,
+    mut query: Query<&mut MaterialEffects, With<Enemy>>,
+) {
+    for event in events.read() {
+        if let Ok(mut effects) = query.get_mut(event.entity) {
+            effects.flash(HIT_FLASH_SECS);
+            effects.tint(HIT_TINT_COLOR, HIT_TINT_SECS);
+        }
+    }
+}
+
+fn on_player_hit_blink(
+    mut events: EventReader<PlayerHitByEnemyEvent>,
+    query: Single<&mut MaterialEffects, With<Player>>,
+) {
+    let mut effects = query.into_inner();
+    for _ in events.read() {
+        effects.blink(PLAYER_INVULNERABILITY_SECS);
+    }
+}
+
+/// The player blinks while the bomb keeps it invulnerable.
+fn on_bomb_blink(
+    mut events: EventReader<BombDetonatedEvent>,
+    query: Single<&mut MaterialEffects, With<Player>>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    query.into_inner().blink(BOMB_INVULNERABILITY_SECS);
+}
+
+fn show_status_tints(mut query: Query<(&StatusEffects, &mut MaterialEffects)>) {
+    for (statuses, mut effects) in &mut query {
+        effects.set_status_tint(statuses.tint());
+    }
+}
+
+fn tick(mut query: Query<&mut MaterialEffects>, time: Res<Time>) {
+    for mut effects in &mut query {
+        effects.tick(time.delta_secs());
+    }
+}
+
+fn release_dissolved(
+    mut wrecks: PoolCommands<Wreck>,
+    query: Query<(Entity, &MaterialEffects), With<Wreck>>,
+) {
+    for (entity, effects) in &query {
+        if effects.is_dissolved() {
+            wrecks.release(entity);
+        }
+    }
+}
+
+/// Dead enemies are despawned at the end of the tick, a wreck dissolves in their place.
+fn spawn_wrecks(
+    mut wrecks: PoolCommands<Wreck>,
+    mut events: EventReader<EnemyDiedEvent>,
+    query: Query<(
+        &Transform,
+        &Mesh2d,
+        &MeshMaterial2d<EffectMaterial>,
+        &SpriteAnimation,
+    )>,
+) {
+    for event in events.read() {
+        let Ok((transform, mesh, material, animation)) = query.get(event.entity) else {
+            continue;
+        };
+
+        let mut effects = MaterialEffects::default();
+        effects.dissolve();
+        wrecks.spawn((
+            Name::new("Wreck"),
+            mesh.clone(),
+            material.clone(),
+            *transform,
+            SpriteAnimation::new(animation.sheet(), AnimationState::Dying),
+            effects,
+            Wreck,
+        ));
+    }
+}
+
+/// Writes frame and effects into the materials, only touching materials that changed.
+fn update_materials(
+    mut materials: ResMut<Assets<EffectMaterial>>,
+    query: Query<(
+        &MeshMaterial2d<EffectMaterial>,
+        Option<&MaterialEffects>,
+        Option<&SpriteAnimation>,
+    )>,
+) {
+    for (handle, effects, animation) in &query {
+        let Some(material) = materials.get(&handle.0) else {
+            continue;
+        };
+
+        let mut params = material.params;
+        if let Some(effects) = effects {
+            effects.apply(&mut params);
+        }
+        if let Some(animation) = animation {
+            params.frame = animation.frame() as u32;
+        }
+
+        if params != material.params
+            && let Some(material) = materials.get_mut(&handle.0)
+        {
+            material.params = params;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn add_material(world: &mut World) -> Handle<EffectMaterial> {
+        world
+            .run_system_once(|mut materials: EffectMaterials| materials.add(Sheet::Creep))
+            .unwrap()
+    }
+
+    #[test]
+    fn released_materials_are_reused() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<TextureAtlasLayout>()
+            .init_asset::<EffectMaterial>()
+            .init_resource::<SpriteSheets>()
+            .init_resource::<EffectMaterialPool>();
+        let world = app.world_mut();
+
+        let mut held: Vec<_> = (0..EFFECT_MATERIAL_POOL_SIZE)
+            .map(|_| add_material(world))
+            .collect();
+        assert_eq!(
+            world.resource::<Assets<EffectMaterial>>().len(),
+            EFFECT_MATERIAL_POOL_SIZE
+        );
+
+        let released = held.pop().unwrap().id();
+        held.push(add_material(world));
+        assert_eq!(held.last().unwrap().id(), released);
+
+        held.push(add_material(world));
+        assert_eq!(
+            world.resource::<Assets<EffectMaterial>>().len(),
+            EFFECT_MATERIAL_POOL_SIZE + 1
+        );
+    }
+
+    #[test]
+    fn effects_wear_off() {
+        let mut effects = MaterialEffects::default();
+        effects.flash(0.1);
+        effects.tint(Color::srgb(1., 0., 0.), 0.5);
+        effects.blink(1.);
+        effects.tick(2.);
+
+        let mut params = EffectParams {
+            tint: Vec4::ONE,
+            flash: 1.,
+            dissolve: 0.,
+            opacity: 0.,
+            frame: 0,
+            frames: 1,
+        };
+        effects.apply(&mut params);
+        assert_eq!(params.flash, 0.);
+        assert_eq!(params.tint, Vec4::ZERO);
+        assert_eq!(params.opacity, 1.);
+    }
+
+    #[test]
+    fn dissolve_completes() {
+        let mut effects = MaterialEffects::default();
+        effects.dissolve();
+        assert!(!effects.is_dissolved());
+        effects.tick(DISSOLVE_SECS);
+        assert!(effects.is_dissolved());
+    }
+}
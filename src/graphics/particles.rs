@@ -0,0 +1,319 @@
+use std::ops::Range;
+
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::view::NoFrustumCulling;
+
+use crate::prelude::*;
+use crate::world::bomb::BombDetonatedEvent;
+
+/// Upper bound of simultaneously alive particles, new particles are dropped once it is reached.
+const MAX_PARTICLES: usize = 4096;
+
+/// Parameters of a particle effect.
+/// Particles start with `colors.0` and `sizes.0` and end with `colors.1` and `sizes.1`,
+/// an end color with zero alpha fades the particle out. Colors brighter than 1 glow with bloom.
+#[derive(Clone, Debug)]
+pub struct ParticleEffect {
+    pub count: usize,
+    /// Direction particles are emitted to, `spread` is the angle in radians around it.
+    pub direction: Vec2,
+    pub spread: f32,
+    pub speed: Range<f32>,
+    /// Exponential slow down, see `physics::LinearDrag`
+    pub drag: f32,
+    pub lifetime: Range<f32>,
+    pub sizes: (f32, f32),
+    pub colors: (Color, Color),
+}
+
+pub const EXPLOSION: ParticleEffect = ParticleEffect {
+    count: 40,
+    direction: Vec2::Y,
+    spread: 2. * std::f32::consts::PI,
+    speed: 100.0..450.0,
+    drag: 3.,
+    lifetime: 0.4..0.9,
+    sizes: (9., 2.),
+    colors: (
+        Color::linear_rgb(6.0, 4.0, 1.5),
+        Color::srgba(1.0, 0.3, 0.1, 0.0),
+    ),
+};
+
+pub const BOMB_BLAST: ParticleEffect = ParticleEffect {
+    count: 400,
+    direction: Vec2::Y,
+    spread: 2. * std::f32::consts::PI,
+    speed: 300.0..1600.0,
+    drag: 2.,
+    lifetime: 0.5..1.2,
+    sizes: (14., 3.),
+    colors: (
+        Color::linear_rgb(5.0, 5.0, 8.0),
+        Color::srgba(0.6, 0.3, 1.0, 0.0),
+    ),
+};
+
+pub const HIT_SPARKS: ParticleEffect = ParticleEffect {
+    count: 8,
+    direction: Vec2::NEG_Y,
+    spread: 1.2,
+    speed: 150.0..350.0,
+    drag: 6.,
+    lifetime: 0.1..0.25,
+    sizes: (4., 1.),
+    colors: (
+        Color::linear_rgb(4.0, 4.0, 2.5),
+        Color::srgba(1.0, 0.6, 0.2, 0.0),
+    ),
+};
+
+pub const ENGINE_TRAIL: ParticleEffect = ParticleEffect {
+    count: 1,
+    direction: Vec2::NEG_Y,
+    spread: 0.3,
+    speed: 150.0..250.0,
+    drag: 1.,
+    lifetime: 0.2..0.35,
+    sizes: (7., 1.),
+    colors: (Color::srgb(0.6, 0.8, 1.0), Color::srgba(0.3, 0.2, 1.0, 0.0)),
+};
+
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    drag: f32,
+    age: f32,
+    lifetime: f32,
+    sizes: (f32, f32),
+    colors: (LinearRgba, LinearRgba),
+}
+
+impl Particle {
+    fn life(&self) -> f32 {
+        (self.age / self.lifetime).min(1.)
+    }
+}
+
+/// All alive particles. They are simulated on the CPU and drawn as a single mesh,
+/// so no entities or assets are created per particle.
+#[derive(Resource)]
+pub struct Particles {
+    particles: Vec<Particle>,
+}
+
+impl Default for Particles {
+    fn default() -> Self {
+        Particles {
+            particles: Vec::with_capacity(MAX_PARTICLES),
+        }
+    }
+}
+
+impl Particles {
+    /// Emits `effect.count` particles at `position`.
+    pub fn burst(&mut self, effect: &ParticleEffect, position: Vec2) {
+        self.emit(effect, position, effect.count);
+    }
+
+    pub fn emit(&mut self, effect: &ParticleEffect, position: Vec2, count: usize) {
+        let count = count.min(MAX_PARTICLES - self.particles.len());
+        let colors = (effect.colors.0.to_linear(), effect.colors.1.to_linear());
+        for _ in 0..count {
+            let angle = rand::random_range(-0.5..=0.5) * effect.spread;
+            let direction = Vec2::from_angle(angle).rotate(effect.direction);
+            self.particles.push(Particle {
+                position,
+                velocity: direction * random_in(&effect.speed),
+                drag: effect.drag,
+                age: 0.,
+                lifetime: random_in(&effect.lifetime),
+                sizes: effect.sizes,
+                colors,
+            });
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+}
+
+fn random_in(range: &Range<f32>) -> f32 {
+    if range.is_empty() {
+        range.start
+    } else {
+        rand::random_range(range.clone())
+    }
+}
+
+/// Continuously emits particles at the position of its entity, e.g. an engine trail.
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub effect: ParticleEffect,
+    /// Particles per second
+    pub rate: f32,
+    pub offset: Vec2,
+    accumulated: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(effect: ParticleEffect, rate: f32, offset: Vec2) -> Self {
+        ParticleEmitter {
+            effect,
+            rate,
+            offset,
+            accumulated: 0.,
+        }
+    }
+}
+
+/// The entity holding the mesh all particles are drawn into.
+#[derive(Component)]
+struct ParticleMesh;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Particles>()
+            .add_systems(Startup, (setup,))
+            .add_systems(
+                Update,
+                (
+                    (
+                        on_enemy_hit_sparks,
+                        on_enemy_died_explosion,
+                        on_bomb_blast,
+                        emit,
+                    ),
+                    simulate,
+                    draw,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let mesh = Mesh::new(PrimitiveTopology::TriangleList, default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, Vec::<[f32; 4]>::new())
+        .with_inserted_indices(Indices::U32(vec![]));
+
+    commands.spawn((
+        Name::new("Particles"),
+        ParticleMesh,
+        Mesh2d(meshes.add(mesh)),
+        MeshMaterial2d(materials.add(Color::WHITE)),
+        Transform::from_xyz(0., 0., 1.),
+        // the mesh changes every frame, its bounds are never up to date
+        NoFrustumCulling,
+    ));
+}
+
+fn on_enemy_hit_sparks(mut particles: ResMut<Particles>, mut events: EventReader<EnemyHitEvent>) {
+    for event in events.read() {
+        particles.burst(&HIT_SPARKS, event.position);
+        // critical hits throw twice the sparks
+        if event.critical {
+            particles.burst(&HIT_SPARKS, event.position);
+        }
+    }
+}
+
+fn on_enemy_died_explosion(
+    mut particles: ResMut<Particles>,
+    mut events: EventReader<EnemyDiedEvent>,
+) {
+    for event in events.read() {
+        particles.burst(&EXPLOSION, event.position);
+    }
+}
+
+fn on_bomb_blast(mut particles: ResMut<Particles>, mut events: EventReader<BombDetonatedEvent>) {
+    for event in events.read() {
+        particles.burst(&BOMB_BLAST, event.position);
+    }
+}
+
+fn emit(
+    mut particles: ResMut<Particles>,
+    mut emitters: Query<(&mut ParticleEmitter, &GlobalTransform)>,
+    time: Res<Time>,
+) {
+    for (mut emitter, transform) in &mut emitters {
+        emitter.accumulated += emitter.rate * time.delta_secs();
+        let count = emitter.accumulated.floor();
+        emitter.accumulated -= count;
+
+        let position = transform.translation().truncate() + emitter.offset;
+        particles.emit(&emitter.effect, position, count as usize);
+    }
+}
+
+fn simulate(mut particles: ResMut<Particles>, time: Res<Time>) {
+    let dt = time.delta_secs();
+    particles.particles.retain_mut(|particle| {
+        particle.age += dt;
+        particle.velocity *= (-particle.drag * dt).exp();
+        particle.position += particle.velocity * dt;
+        particle.age < particle.lifetime
+    });
+}
+
+/// Writes every particle as a quad into the particle mesh.
+fn draw(
+    particles: Res<Particles>,
+    mesh: Single<&Mesh2d, With<ParticleMesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Some(mesh) = meshes.get_mut(&mesh.0) else {
+        return;
+    };
+
+    let n = particles.len();
+    let mut positions = Vec::with_capacity(n * 4);
+    let mut colors = Vec::with_capacity(n * 4);
+    let mut indices = Vec::with_capacity(n * 6);
+
+    for (i, particle) in particles.particles.iter().enumerate() {
+        let t = particle.life();
+        let half = particle.sizes.0.lerp(particle.sizes.1, t) / 2.;
+        let color = particle.colors.0.mix(&particle.colors.1, t).to_f32_array();
+        let p = particle.position;
+
+        positions.extend([
+            [p.x - half, p.y - half, 0.],
+            [p.x + half, p.y - half, 0.],
+            [p.x + half, p.y + half, 0.],
+            [p.x - half, p.y + half, 0.],
+        ]);
+        colors.extend([color; 4]);
+
+        let base = (i * 4) as u32;
+        indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_indices(Indices::U32(indices));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_respects_capacity() {
+        let mut particles = Particles::default();
+        for _ in 0..MAX_PARTICLES {
+            particles.burst(&EXPLOSION, Vec2::ZERO);
+        }
+        assert_eq!(particles.len(), MAX_PARTICLES);
+    }
+}
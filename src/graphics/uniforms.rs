@@ -0,0 +1,40 @@
+/// Uniforms shared with the shaders in assets/shaders, in one module so dead code is only allowed here.
+use bevy::render::extract_component::ExtractComponent;
+use bevy::render::render_resource::ShaderType;
+
+use crate::prelude::*;
+
+/// Uniform of `EffectMaterial`, must match `EffectParams` in assets/shaders/effects.wgsl.
+#[derive(ShaderType, Clone, Copy, Debug, PartialEq)]
+pub struct EffectParams {
+    /// Tint color, alpha is the strength of the tint
+    pub tint: Vec4,
+    /// Mix towards white in `0..=1`
+    pub flash: f32,
+    /// Fraction of the sprite that is dissolved in `0..=1`
+    pub dissolve: f32,
+    pub opacity: f32,
+    pub frame: u32,
+    pub frames: u32,
+}
+
+/// Enables the CRT filter on a camera, must match `CrtSettings` in assets/shaders/crt.wgsl.
+#[derive(Component, ExtractComponent, ShaderType, Clone, Copy, Debug)]
+pub struct CrtSettings {
+    pub scanline_intensity: f32,
+    /// Number of scanlines over the height of the screen
+    pub scanline_count: f32,
+    pub curvature: f32,
+    pub vignette: f32,
+}
+
+impl Default for CrtSettings {
+    fn default() -> Self {
+        CrtSettings {
+            scanline_intensity: 0.25,
+            scanline_count: 360.,
+            curvature: 0.04,
+            vignette: 0.3,
+        }
+    }
+}
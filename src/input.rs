@@ -0,0 +1,481 @@
+/// Maps physical inputs to game actions, gameplay reads `ActionState` instead of the keyboard.
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use bevy::input::InputSystem;
+use bevy::window::PrimaryWindow;
+use gamepad::GamepadSupportPlugin;
+use serde::{Deserialize, Serialize};
+
+use crate::graphics::camera::MainCamera;
+use crate::prelude::*;
+use crate::settings::Settings;
+
+pub mod gamepad;
+
+/// `MoveX` and `MoveY` are axes in `-1..=1`, the other actions are buttons.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    MoveX,
+    MoveY,
+    Fire,
+    Bomb,
+    SwitchWeapon,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 6] = [
+        Action::MoveX,
+        Action::MoveY,
+        Action::Fire,
+        Action::Bomb,
+        Action::SwitchWeapon,
+        Action::Pause,
+    ];
+
+    /// Each end of an axis is bound on its own, buttons are bound with 1.
+    pub const BINDINGS: [(Action, f32); 8] = [
+        (Action::MoveX, -1.),
+        (Action::MoveX, 1.),
+        (Action::MoveY, -1.),
+        (Action::MoveY, 1.),
+        (Action::Fire, 1.),
+        (Action::Bomb, 1.),
+        (Action::SwitchWeapon, 1.),
+        (Action::Pause, 1.),
+    ];
+
+    /// Name of the binding contributing `value` to the action.
+    pub fn label(&self, value: f32) -> &'static str {
+        match self {
+            Action::MoveX if value < 0. => "Move left",
+            Action::MoveX => "Move right",
+            Action::MoveY if value < 0. => "Move down",
+            Action::MoveY => "Move up",
+            Action::Fire => "Fire",
+            Action::Bomb => "Bomb",
+            Action::SwitchWeapon => "Switch weapon",
+            Action::Pause => "Pause",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControlScheme {
+    /// The ship always fires straight ahead
+    Classic,
+    /// The ship aims at the mouse cursor or with the right stick,
+    /// the left mouse button and tilting the right stick fire
+    TwinStick,
+}
+
+/// Keys and gamepad buttons bound to each action with the value they contribute while held,
+/// e.g. -1 for moving left on `MoveX`. The values of all held inputs are added up.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct InputSettings {
+    pub bindings: BTreeMap<Action, Vec<(KeyCode, f32)>>,
+    pub gamepad_bindings: BTreeMap<Action, Vec<(GamepadButton, f32)>>,
+    pub rumble: bool,
+    pub scheme: ControlScheme,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        let bindings = [
+            (
+                Action::MoveX,
+                vec![
+                    (KeyCode::ArrowLeft, -1.),
+                    (KeyCode::KeyA, -1.),
+                    (KeyCode::ArrowRight, 1.),
+                    (KeyCode::KeyD, 1.),
+                ],
+            ),
+            (
+                Action::MoveY,
+                vec![
+                    (KeyCode::ArrowDown, -1.),
+                    (KeyCode::KeyS, -1.),
+                    (KeyCode::ArrowUp, 1.),
+                    (KeyCode::KeyW, 1.),
+                ],
+            ),
+            (Action::Fire, vec![(KeyCode::Space, 1.)]),
+            (Action::Bomb, vec![(KeyCode::KeyB, 1.)]),
+            (Action::SwitchWeapon, vec![(KeyCode::Tab, 1.)]),
+            (Action::Pause, vec![(KeyCode::Escape, 1.)]),
+        ];
+        let gamepad_bindings = [
+            (
+                Action::MoveX,
+                vec![
+                    (GamepadButton::DPadLeft, -1.),
+                    (GamepadButton::DPadRight, 1.),
+                ],
+            ),
+            (
+                Action::MoveY,
+                vec![(GamepadButton::DPadDown, -1.), (GamepadButton::DPadUp, 1.)],
+            ),
+            (Action::Fire, vec![(GamepadButton::South, 1.)]),
+            (Action::Bomb, vec![(GamepadButton::RightTrigger2, 1.)]),
+            (Action::SwitchWeapon, vec![(GamepadButton::North, 1.)]),
+            (Action::Pause, vec![(GamepadButton::Start, 1.)]),
+        ];
+        InputSettings {
+            bindings: bindings.into_iter().collect(),
+            gamepad_bindings: gamepad_bindings.into_iter().collect(),
+            rumble: true,
+            scheme: ControlScheme::Classic,
+        }
+    }
+}
+
+impl InputSettings {
+    pub fn keys(&self, action: Action) -> &[(KeyCode, f32)] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn buttons(&self, action: Action) -> &[(GamepadButton, f32)] {
+        self.gamepad_bindings
+            .get(&action)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Binds `key` alone to contribute `value` to `action`, keys contributing other values stay bound.
+    pub fn rebind(&mut self, action: Action, value: f32, key: KeyCode) {
+        let keys = self.bindings.entry(action).or_default();
+        keys.retain(|(_, bound)| *bound != value);
+        keys.push((key, value));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AimInput {
+    Direction(Vec2),
+    /// World position of the mouse cursor
+    Cursor(Vec2),
+}
+
+/// Actions of the current frame, updated before `Update` like `ButtonInput`.
+#[derive(Resource, Default, Debug)]
+pub struct ActionState {
+    /// Values of the actions with any input held, in `-1..=1`. Analog sticks give values in between.
+    values: HashMap<Action, f32>,
+    just_pressed: HashSet<Action>,
+    /// Actions pressed since the last fixed tick
+    latched: HashSet<Action>,
+    aim: Option<AimInput>,
+}
+
+impl ActionState {
+    pub fn value(&self, action: Action) -> f32 {
+        self.values.get(&action).copied().unwrap_or(0.)
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.values.contains_key(&action)
+    }
+
+    /// Whether `action` was pressed this frame, for systems running once per frame.
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Whether `action` was pressed since the last fixed tick, for systems in `FixedUpdate`.
+    /// A frame runs any number of fixed ticks, a press is kept until a tick has seen it.
+    pub fn fixed_just_pressed(&self, action: Action) -> bool {
+        self.latched.contains(&action)
+    }
+
+    /// The `MoveX` and `MoveY` axes, at most 1 long so diagonals are not faster.
+    pub fn movement(&self) -> Vec2 {
+        Vec2::new(self.value(Action::MoveX), self.value(Action::MoveY)).clamp_length_max(1.)
+    }
+
+    /// Direction aimed at from `from` with the twin-stick scheme, if the player aims anywhere.
+    pub fn aim(&self, from: Vec2) -> Option<Vec2> {
+        match self.aim? {
+            AimInput::Direction(direction) => direction.try_normalize(),
+            AimInput::Cursor(cursor) => (cursor - from).try_normalize(),
+        }
+    }
+
+    /// World position of the mouse cursor, if the ship aims at it with the twin-stick scheme.
+    pub fn cursor(&self) -> Option<Vec2> {
+        match self.aim? {
+            AimInput::Cursor(cursor) => Some(cursor),
+            AimInput::Direction(_) => None,
+        }
+    }
+
+    fn update(
+        &mut self,
+        settings: &InputSettings,
+        keyboard: &ButtonInput<KeyCode>,
+        gamepads: &[&Gamepad],
+    ) {
+        let keys = |action| settings.keys(action).iter().map(|(key, _)| *key);
+        let buttons = |action| settings.buttons(action).iter().map(|(button, _)| *button);
+        let stick: Vec2 = gamepads.iter().map(|g| gamepad::stick(g)).sum();
+        self.values = Action::ALL
+            .into_iter()
+            .filter_map(|action| {
+                let held_keys = settings
+                    .keys(action)
+                    .iter()
+                    .filter(|(key, _)| keyboard.pressed(*key))
+                    .map(|(_, value)| *value);
+                let held_buttons = settings
+                    .buttons(action)
+                    .iter()
+                    .filter(|(button, _)| gamepads.iter().any(|g| g.pressed(*button)))
+                    .map(|(_, value)| *value);
+                let analog = match action {
+                    Action::MoveX => stick.x,
+                    Action::MoveY => stick.y,
+                    _ => 0.,
+                };
+                let held: Vec<f32> = held_keys.chain(held_buttons).collect();
+                if held.is_empty() && analog == 0. {
+                    return None;
+                }
+                let value = held.iter().sum::<f32>() + analog;
+                Some((action, value.clamp(-1., 1.)))
+            })
+            .collect();
+        self.just_pressed = Action::ALL
+            .into_iter()
+            .filter(|action| {
+                keyboard.any_just_pressed(keys(*action))
+                    || gamepads
+                        .iter()
+                        .any(|g| g.any_just_pressed(buttons(*action)))
+            })
+            .collect();
+    }
+
+    /// The right stick wins over the mouse cursor.
+    fn update_aim(
+        &mut self,
+        settings: &InputSettings,
+        mouse: &ButtonInput<MouseButton>,
+        cursor: Option<Vec2>,
+        gamepads: &[&Gamepad],
+    ) {
+        self.aim = None;
+        if settings.scheme != ControlScheme::TwinStick {
+            return;
+        }
+
+        let stick = gamepads
+            .iter()
+            .map(|g| gamepad::aim_stick(g))
+            .find(|stick| *stick != Vec2::ZERO);
+        self.aim = stick
+            .map(AimInput::Direction)
+            .or(cursor.map(AimInput::Cursor));
+
+        if stick.is_some() || mouse.pressed(MouseButton::Left) {
+            self.values.insert(Action::Fire, 1.);
+        }
+        if mouse.just_pressed(MouseButton::Left) {
+            self.just_pressed.insert(Action::Fire);
+        }
+    }
+
+    fn latch(&mut self) {
+        self.latched.extend(self.just_pressed.iter().copied());
+    }
+}
+
+pub struct GameInputPlugin;
+
+impl Plugin for GameInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(GamepadSupportPlugin)
+            .init_resource::<ActionState>()
+            .add_systems(
+                PreUpdate,
+                (update_action_state, latch_actions)
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(FixedPostUpdate, clear_latched_actions);
+    }
+}
+
+fn update_action_state(
+    mut actions: ResMut<ActionState>,
+    settings: Res<Settings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    let gamepads: Vec<_> = gamepads.iter().collect();
+    actions.update(&settings.input, &keyboard, &gamepads);
+
+    // the cursor is relative to the window, the camera may only render to a part of it
+    let (camera, camera_transform) = *camera;
+    let cursor = window.cursor_position().and_then(|cursor| {
+        let viewport_min = camera.logical_viewport_rect()?.min;
+        camera
+            .viewport_to_world_2d(camera_transform, cursor - viewport_min)
+            .ok()
+    });
+    actions.update_aim(&settings.input, &mouse, cursor, &gamepads);
+}
+
+/// No fixed ticks run while paused, presses in the menu must not reach the game.
+fn latch_actions(mut actions: ResMut<ActionState>, time: Res<Time<Virtual>>) {
+    if !time.is_paused() {
+        actions.latch();
+    }
+}
+
+fn clear_latched_actions(mut actions: ResMut<ActionState>) {
+    actions.latched.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_map_to_actions() {
+        let mut settings = InputSettings::default();
+        settings.rebind(Action::Fire, 1., KeyCode::KeyF);
+        let mut keyboard = ButtonInput::default();
+        keyboard.press(KeyCode::KeyF);
+        keyboard.press(KeyCode::ArrowLeft);
+        keyboard.press(KeyCode::KeyW);
+        keyboard.press(KeyCode::Space);
+
+        let mut actions = ActionState::default();
+        actions.update(&settings, &keyboard, &[]);
+        assert!(actions.just_pressed(Action::Fire));
+        assert_eq!(actions.value(Action::MoveX), -1.);
+        assert_eq!(actions.value(Action::MoveY), 1.);
+        assert_eq!(actions.movement(), Vec2::new(-1., 1.).normalize());
+
+        // the old binding is gone
+        keyboard.clear();
+        keyboard.release(KeyCode::KeyF);
+        actions.update(&settings, &keyboard, &[]);
+        assert!(!actions.pressed(Action::Fire));
+        assert!(!actions.just_pressed(Action::Fire));
+    }
+
+    #[test]
+    fn opposite_keys_cancel_out() {
+        let mut settings = InputSettings::default();
+        settings.rebind(Action::MoveX, 1., KeyCode::KeyL);
+        let mut keyboard = ButtonInput::default();
+        keyboard.press(KeyCode::KeyA);
+        keyboard.press(KeyCode::ArrowLeft);
+
+        // no faster than a full tilt
+        let mut actions = ActionState::default();
+        actions.update(&settings, &keyboard, &[]);
+        assert_eq!(actions.value(Action::MoveX), -1.);
+
+        keyboard.release(KeyCode::ArrowLeft);
+        keyboard.press(KeyCode::KeyL);
+        actions.update(&settings, &keyboard, &[]);
+        assert_eq!(actions.value(Action::MoveX), 0.);
+        assert!(actions.pressed(Action::MoveX));
+
+        // the old right keys are gone
+        keyboard.release_all();
+        keyboard.press(KeyCode::KeyD);
+        actions.update(&settings, &keyboard, &[]);
+        assert!(!actions.pressed(Action::MoveX));
+    }
+
+    #[test]
+    fn presses_last_until_a_fixed_tick() {
+        #[derive(Resource, Default)]
+        struct Bombs(u32);
+
+        let mut app = App::new();
+        app.init_resource::<ActionState>()
+            .init_resource::<Bombs>()
+            .add_systems(
+                FixedUpdate,
+                |actions: Res<ActionState>, mut bombs: ResMut<Bombs>| {
+                    if actions.fixed_just_pressed(Action::Bomb) {
+                        bombs.0 += 1;
+                    }
+                },
+            )
+            .add_systems(FixedPostUpdate, clear_latched_actions);
+        let fixed_tick = |app: &mut App| {
+            app.world_mut().run_schedule(FixedUpdate);
+            app.world_mut().run_schedule(FixedPostUpdate);
+        };
+        let frame = |app: &mut App, keyboard: &ButtonInput<KeyCode>| {
+            let mut actions = app.world_mut().resource_mut::<ActionState>();
+            actions.update(&InputSettings::default(), keyboard, &[]);
+            actions.latch();
+        };
+
+        // pressed in a frame without a fixed tick, held in a frame with two
+        let mut keyboard = ButtonInput::default();
+        keyboard.press(KeyCode::KeyB);
+        frame(&mut app, &keyboard);
+        keyboard.clear();
+        frame(&mut app, &keyboard);
+        fixed_tick(&mut app);
+        fixed_tick(&mut app);
+        assert_eq!(app.world().resource::<Bombs>().0, 1);
+
+        // pressed again in a frame with two fixed ticks
+        keyboard.release(KeyCode::KeyB);
+        keyboard.clear();
+        keyboard.press(KeyCode::KeyB);
+        frame(&mut app, &keyboard);
+        fixed_tick(&mut app);
+        fixed_tick(&mut app);
+        assert_eq!(app.world().resource::<Bombs>().0, 2);
+    }
+
+    #[test]
+    fn gamepads_map_to_actions() {
+        let settings = InputSettings::default();
+        let mut gamepad = Gamepad::default();
+        gamepad.digital_mut().press(GamepadButton::South);
+        gamepad.analog_mut().set(GamepadAxis::LeftStickX, 0.6);
+
+        let mut actions = ActionState::default();
+        actions.update(&settings, &ButtonInput::default(), &[&gamepad]);
+        assert!(actions.pressed(Action::Fire));
+        // slower than full speed, the stick is only tilted
+        assert!(actions.movement().x > 0. && actions.movement().x < 0.6);
+        assert_eq!(actions.movement().y, 0.);
+    }
+
+    #[test]
+    fn twin_stick_aims() {
+        let mut settings = InputSettings::default();
+        let mouse = ButtonInput::default();
+        let cursor = Some(Vec2::new(10., 20.));
+        let mut actions = ActionState::default();
+
+        actions.update_aim(&settings, &mouse, cursor, &[]);
+        assert_eq!(actions.aim(Vec2::ZERO), None);
+
+        settings.scheme = ControlScheme::TwinStick;
+        actions.update_aim(&settings, &mouse, cursor, &[]);
+        assert_eq!(actions.aim(Vec2::new(10., 0.)), Some(Vec2::Y));
+
+        // the right stick wins and fires
+        let mut gamepad = Gamepad::default();
+        gamepad.analog_mut().set(GamepadAxis::RightStickX, -1.);
+        actions.update_aim(&settings, &mouse, cursor, &[&gamepad]);
+        assert_eq!(actions.aim(Vec2::ZERO), Some(Vec2::NEG_X));
+        assert!(actions.pressed(Action::Fire));
+    }
+}
@@ -0,0 +1,123 @@
+/// Gamepad support on top of the actions: analog movement and aiming, rumble and hot-plugging.
+/// Buttons are bound like keys, see `InputSettings::buttons`.
+use std::time::Duration;
+
+use bevy::input::gamepad::{
+    GamepadConnection, GamepadConnectionEvent, GamepadRumbleIntensity, GamepadRumbleRequest,
+};
+
+use crate::prelude::constants::*;
+use crate::prelude::*;
+use crate::settings::Settings;
+use crate::world::bomb::BombDetonatedEvent;
+use crate::world::player::PlayerHitByEnemyEvent;
+
+/// Shakes all connected gamepads, e.g. on hits or bombs. `strength` is in `0..=1`.
+#[derive(Event)]
+pub struct Rumble {
+    pub strength: f32,
+    pub secs: f32,
+}
+
+/// The left stick, proportional but without the drift of a stick at rest.
+pub(super) fn stick(gamepad: &Gamepad) -> Vec2 {
+    deadzone(gamepad.left_stick())
+}
+
+/// The right stick, aiming with the twin-stick scheme.
+pub(super) fn aim_stick(gamepad: &Gamepad) -> Vec2 {
+    deadzone(gamepad.right_stick())
+}
+
+/// Removes `GAMEPAD_DEADZONE` around the center, rescaled so the movement
+/// still starts at zero at the edge of the deadzone.
+fn deadzone(stick: Vec2) -> Vec2 {
+    let length = stick.length();
+    if length <= GAMEPAD_DEADZONE {
+        return Vec2::ZERO;
+    }
+    let scaled = ((length - GAMEPAD_DEADZONE) / (1. - GAMEPAD_DEADZONE)).min(1.);
+    stick / length * scaled
+}
+
+pub struct GamepadSupportPlugin;
+
+impl Plugin for GamepadSupportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Rumble>().add_systems(
+            Update,
+            (
+                log_connections,
+                ((on_player_hit_rumble, on_bomb_rumble), rumble).chain(),
+            ),
+        );
+    }
+}
+
+/// Gamepads can be plugged in at any time, each connected gamepad controls the player.
+fn log_connections(mut events: EventReader<GamepadConnectionEvent>) {
+    for event in events.read() {
+        match &event.connection {
+            GamepadConnection::Connected { name, .. } => info!("Gamepad connected: {name}"),
+            GamepadConnection::Disconnected => info!("Gamepad disconnected"),
+        }
+    }
+}
+
+fn on_player_hit_rumble(
+    mut events: EventReader<PlayerHitByEnemyEvent>,
+    mut rumble: EventWriter<Rumble>,
+) {
+    for _ in events.read() {
+        rumble.send(Rumble {
+            strength: PLAYER_HIT_RUMBLE,
+            secs: PLAYER_HIT_RUMBLE_SECS,
+        });
+    }
+}
+
+fn on_bomb_rumble(mut events: EventReader<BombDetonatedEvent>, mut rumble: EventWriter<Rumble>) {
+    for _ in events.read() {
+        rumble.send(Rumble {
+            strength: BOMB_RUMBLE,
+            secs: BOMB_RUMBLE_SECS,
+        });
+    }
+}
+
+fn rumble(
+    mut events: EventReader<Rumble>,
+    mut requests: EventWriter<GamepadRumbleRequest>,
+    settings: Res<Settings>,
+    gamepads: Query<Entity, With<Gamepad>>,
+) {
+    for event in events.read() {
+        if !settings.input.rumble {
+            continue;
+        }
+        for gamepad in &gamepads {
+            requests.send(GamepadRumbleRequest::Add {
+                duration: Duration::from_secs_f32(event.secs),
+                intensity: GamepadRumbleIntensity {
+                    strong_motor: event.strength,
+                    weak_motor: event.strength,
+                },
+                gamepad,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadzone_is_removed() {
+        assert_eq!(deadzone(Vec2::new(0.1, -0.1)), Vec2::ZERO);
+        assert_eq!(deadzone(Vec2::X), Vec2::X);
+        // halfway between deadzone and edge moves at half speed
+        let half = deadzone(Vec2::new(0., -(1. + GAMEPAD_DEADZONE) / 2.));
+        assert!((half.y + 0.5).abs() < 1e-5);
+    }
+}
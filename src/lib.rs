@@ -1,25 +1,32 @@
+mod audio;
 mod constants;
+mod game_assets;
 mod graphics;
 mod helpers;
+mod input;
 mod physics;
+pub mod pool;
 mod prelude;
+mod schedule;
+mod settings;
 mod ui;
 mod world;
 
-use constants::{VIEWPORT_HEIGHT, VIEWPORT_WIDTH};
-
 use crate::{
-    graphics::GraphicsPlugin, physics::PhysicsPlugin, prelude::*, ui::UiPlugin, world::WorldPlugin,
+    audio::GameAudioPlugin, game_assets::GameAssetsPlugin, graphics::GraphicsPlugin,
+    input::GameInputPlugin, physics::PhysicsPlugin, prelude::*, schedule::SchedulePlugin,
+    settings::Settings, settings::SettingsPlugin, ui::UiPlugin, world::WorldPlugin,
 };
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
+        // the window is created from the settings, so they are loaded first
+        app.add_plugins(SettingsPlugin);
         let primary_window = Window {
             title: "Spacedogs".to_string(),
-            resolution: (VIEWPORT_WIDTH / 2., VIEWPORT_HEIGHT / 2.).into(),
-            ..default()
+            ..app.world().resource::<Settings>().display.window()
         };
         app.add_plugins((
             DefaultPlugins
@@ -28,10 +35,14 @@ impl Plugin for GamePlugin {
                     ..default()
                 })
                 .set(ImagePlugin::default_nearest()),
+            SchedulePlugin,
+            GameInputPlugin,
+            GameAssetsPlugin,
             GraphicsPlugin,
             PhysicsPlugin,
             WorldPlugin,
             UiPlugin,
+            GameAudioPlugin,
         ));
     }
 }
@@ -1,19 +1,98 @@
 use crate::prelude::*;
 
-use bevy::{
-    ecs::entity,
-    math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume},
-};
+use bevy::math::bounding::{Aabb2d, IntersectsVolume};
+use steering::SteeringPlugin;
 
-#[derive(Component)]
-pub struct Momentum(pub Vec2);
+pub mod spatial_query;
+pub mod steering;
 
-#[derive(Component, Deref, DerefMut)]
+#[derive(Component, Default, Deref, DerefMut)]
+#[require(Interpolated)]
 pub struct Velocity(pub Vec2);
 
+/// Change of `Velocity` per second.
+#[derive(Component, Default, Deref, DerefMut)]
+#[require(Velocity)]
+pub struct Acceleration(pub Vec2);
+
+/// Exponential slow down of `Velocity`, the fraction of speed lost per second is `1 - e^-drag`.
+#[derive(Component, Deref, DerefMut)]
+pub struct LinearDrag(pub f32);
+
+/// Upper bound for the length of `Velocity`.
+#[derive(Component, Deref, DerefMut)]
+pub struct MaxSpeed(pub f32);
+
+/// Rotation around the z-axis in radians per second.
+#[derive(Component, Deref, DerefMut)]
+#[require(Interpolated)]
+pub struct AngularVelocity(pub f32);
+
+/// Smooths the rendered translation between two fixed timesteps.
+/// During `FixedUpdate` the `Transform` holds the simulated state,
+/// outside of it the translation is interpolated by the overstep of the fixed clock.
+#[derive(Component, Default)]
+pub struct Interpolated {
+    start: Option<(Vec3, Quat)>,
+    end: Option<(Vec3, Quat)>,
+}
+
 #[derive(Component)]
 pub struct Collider;
 
+/// Bit mask of collision layers, one bit per layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LayerMask(pub u32);
+
+impl LayerMask {
+    pub const ALL: LayerMask = LayerMask(u32::MAX);
+    pub const PLAYER: LayerMask = LayerMask(1 << 0);
+    pub const ENEMY: LayerMask = LayerMask(1 << 1);
+    pub const PLAYER_PROJECTILE: LayerMask = LayerMask(1 << 2);
+    pub const ENEMY_PROJECTILE: LayerMask = LayerMask(1 << 3);
+    pub const PICKUP: LayerMask = LayerMask(1 << 4);
+
+    pub fn intersects(self, other: LayerMask) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl std::ops::BitOr for LayerMask {
+    type Output = LayerMask;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        LayerMask(self.0 | rhs.0)
+    }
+}
+
+/// The layers a `Collider` is part of and the layers it can collide with.
+/// Colliders without this component are on all layers and collide with everything.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CollisionLayers {
+    pub memberships: LayerMask,
+    pub filters: LayerMask,
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        Self::new(LayerMask::ALL, LayerMask::ALL)
+    }
+}
+
+impl CollisionLayers {
+    pub fn new(memberships: LayerMask, filters: LayerMask) -> Self {
+        Self {
+            memberships,
+            filters,
+        }
+    }
+
+    /// Two colliders only collide if each one is in the filter of the other.
+    pub fn interacts_with(&self, other: &CollisionLayers) -> bool {
+        self.memberships.intersects(other.filters) && other.memberships.intersects(self.filters)
+    }
+}
+
 #[derive(Event)]
 pub struct CollisionEvent {
     pub entity1: Entity,
@@ -24,11 +103,84 @@ pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(FixedUpdate, (apply_velocity, check_for_collisions))
+        app.add_plugins(SteeringPlugin)
+            .add_systems(
+                FixedUpdate,
+                (
+                    (
+                        apply_acceleration,
+                        apply_drag,
+                        limit_speed,
+                        apply_velocity,
+                        apply_angular_velocity,
+                    )
+                        .chain()
+                        .in_set(GameSet::Movement),
+                    check_for_collisions.in_set(GameSet::Collision),
+                ),
+            )
+            .add_systems(FixedFirst, interpolation_start)
+            .add_systems(FixedLast, interpolation_end)
+            .add_systems(
+                RunFixedMainLoop,
+                (
+                    restore_simulated.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+                    interpolate.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+                ),
+            )
             .add_event::<CollisionEvent>();
     }
 }
 
+fn restore_simulated(mut query: Query<(&mut Transform, &Interpolated)>) {
+    for (mut transform, interpolated) in &mut query {
+        if let Some((translation, rotation)) = interpolated.end {
+            transform.translation = translation;
+            transform.rotation = rotation;
+        }
+    }
+}
+
+fn interpolation_start(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in &mut query {
+        interpolated.start = Some((transform.translation, transform.rotation));
+    }
+}
+
+fn interpolation_end(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in &mut query {
+        interpolated.end = Some((transform.translation, transform.rotation));
+    }
+}
+
+fn interpolate(mut query: Query<(&mut Transform, &Interpolated)>, time: Res<Time<Fixed>>) {
+    let alpha = time.overstep_fraction();
+    for (mut transform, interpolated) in &mut query {
+        if let (Some(start), Some(end)) = (interpolated.start, interpolated.end) {
+            transform.translation = start.0.lerp(end.0, alpha);
+            transform.rotation = start.1.slerp(end.1, alpha);
+        }
+    }
+}
+
+fn apply_acceleration(mut query: Query<(&mut Velocity, &Acceleration)>, time: Res<Time>) {
+    for (mut velocity, acceleration) in &mut query {
+        velocity.0 += acceleration.0 * time.delta_secs();
+    }
+}
+
+fn apply_drag(mut query: Query<(&mut Velocity, &LinearDrag)>, time: Res<Time>) {
+    for (mut velocity, drag) in &mut query {
+        velocity.0 *= (-drag.0 * time.delta_secs()).exp();
+    }
+}
+
+fn limit_speed(mut query: Query<(&mut Velocity, &MaxSpeed)>) {
+    for (mut velocity, max_speed) in &mut query {
+        velocity.0 = velocity.0.clamp_length_max(max_speed.0);
+    }
+}
+
 fn apply_velocity(mut query: Query<(&mut Transform, &Velocity)>, time: Res<Time>) {
     for (mut transform, velocity) in &mut query {
         transform.translation.x += velocity.x * time.delta_secs();
@@ -36,20 +188,28 @@ fn apply_velocity(mut query: Query<(&mut Transform, &Velocity)>, time: Res<Time>
     }
 }
 
+fn apply_angular_velocity(mut query: Query<(&mut Transform, &AngularVelocity)>, time: Res<Time>) {
+    for (mut transform, angular_velocity) in &mut query {
+        transform.rotate_z(angular_velocity.0 * time.delta_secs());
+    }
+}
+
 fn check_for_collisions(
-    mut q: Query<(Entity, &Transform, &Name), With<Collider>>,
+    q: Query<(Entity, &Transform, Option<&CollisionLayers>), With<Collider>>,
     mut colission_events: EventWriter<CollisionEvent>,
 ) {
-    let mut combos = q.iter_combinations_mut();
-    while let Some([(e1, trans1, name1), (e2, trans2, name2)]) = combos.fetch_next() {
+    for [(e1, trans1, layers1), (e2, trans2, layers2)] in q.iter_combinations() {
         if e1 == e2 {
             continue;
         }
 
-        let collision = collide(
-            Aabb2d::new(trans1.translation.truncate(), trans1.scale.truncate()),
-            Aabb2d::new(trans2.translation.truncate(), trans2.scale.truncate()),
-        );
+        let layers1 = layers1.copied().unwrap_or_default();
+        let layers2 = layers2.copied().unwrap_or_default();
+        if !layers1.interacts_with(&layers2) {
+            continue;
+        }
+
+        let collision = collide(collider_aabb(trans1), collider_aabb(trans2));
 
         if collision {
             colission_events.send(CollisionEvent {
@@ -110,6 +270,12 @@ fn check_for_collisions(
 //    }
 //}
 
+/// The bounding box of a collider, its size is given by the scale of the transform.
+#[inline]
+pub(crate) fn collider_aabb(transform: &Transform) -> Aabb2d {
+    Aabb2d::new(transform.translation.truncate(), transform.scale.truncate())
+}
+
 #[inline]
 fn collide(a: Aabb2d, b: Aabb2d) -> bool {
     return a.intersects(&b);
@@ -0,0 +1,256 @@
+/// Queries against the set of colliders, e.g. for beams, line of sight checks or blast radii.
+use bevy::ecs::system::SystemParam;
+use bevy::math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume, RayCast2d};
+
+use crate::prelude::*;
+
+use super::{Collider, CollisionLayers, LayerMask, collider_aabb};
+
+/// The closest collider hit by a ray.
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    pub entity: Entity,
+    pub distance: f32,
+    pub point: Vec2,
+}
+
+/// System param to ask the physics which colliders are at a point, along a ray or in an area.
+/// Only colliders whose memberships intersect the given `filter` are considered.
+///
+/// Note that this reads `Transform`, so systems using it cannot mutably query transforms at the same time.
+#[derive(SystemParam)]
+pub struct SpatialQuery<'w, 's> {
+    colliders: Query<
+        'w,
+        's,
+        (Entity, &'static Transform, Option<&'static CollisionLayers>),
+        With<Collider>,
+    >,
+}
+
+impl SpatialQuery<'_, '_> {
+    fn filtered(&self, filter: LayerMask) -> impl Iterator<Item = (Entity, Aabb2d)> + '_ {
+        self.colliders
+            .iter()
+            .filter(move |(_, _, layers)| {
+                layers
+                    .copied()
+                    .unwrap_or_default()
+                    .memberships
+                    .intersects(filter)
+            })
+            .map(|(entity, transform, _)| (entity, collider_aabb(transform)))
+    }
+
+    /// Casts a ray and returns the first collider hit within `max_distance`.
+    pub fn cast_ray(
+        &self,
+        origin: Vec2,
+        direction: Dir2,
+        max_distance: f32,
+        filter: LayerMask,
+    ) -> Option<RayHit> {
+        let ray = RayCast2d::new(origin, direction, max_distance);
+        self.filtered(filter)
+            .filter_map(|(entity, aabb)| {
+                ray.aabb_intersection_at(&aabb)
+                    .map(|distance| (entity, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entity, distance)| RayHit {
+                entity,
+                distance,
+                point: origin + *direction * distance,
+            })
+    }
+
+    /// Casts a ray and returns all colliders hit within `max_distance`, ordered by distance.
+    pub fn cast_ray_all(
+        &self,
+        origin: Vec2,
+        direction: Dir2,
+        max_distance: f32,
+        filter: LayerMask,
+    ) -> Vec<RayHit> {
+        let ray = RayCast2d::new(origin, direction, max_distance);
+        let mut hits: Vec<RayHit> = self
+            .filtered(filter)
+            .filter_map(|(entity, aabb)| {
+                ray.aabb_intersection_at(&aabb).map(|distance| RayHit {
+                    entity,
+                    distance,
+                    point: origin + *direction * distance,
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// Returns all colliders containing `point`.
+    pub fn point_intersections(&self, point: Vec2, filter: LayerMask) -> Vec<Entity> {
+        self.filtered(filter)
+            .filter(|(_, aabb)| aabb.closest_point(point) == point)
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    /// Returns all colliders overlapping a circle, e.g. a blast radius.
+    pub fn circle_intersections(
+        &self,
+        center: Vec2,
+        radius: f32,
+        filter: LayerMask,
+    ) -> Vec<Entity> {
+        let circle = BoundingCircle::new(center, radius);
+        self.filtered(filter)
+            .filter(|(_, aabb)| circle.intersects(aabb))
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    /// Returns all colliders overlapping a rectangle.
+    pub fn aabb_intersections(&self, aabb: Aabb2d, filter: LayerMask) -> Vec<Entity> {
+        self.filtered(filter)
+            .filter(|(_, other)| aabb.intersects(other))
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    /// True if no collider in `filter` blocks the line between `from` and `to`.
+    pub fn line_of_sight(&self, from: Vec2, to: Vec2, filter: LayerMask) -> bool {
+        let Ok((direction, distance)) = Dir2::new_and_length(to - from) else {
+            return true;
+        };
+        self.cast_ray(from, direction, distance, filter).is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// Three enemies in a row at x = 0, 100 and 200 and a pickup above the middle one,
+    /// all of them 20 units wide.
+    fn spawn_colliders(world: &mut World) -> [Entity; 4] {
+        let mut spawn = |x, y, layer| {
+            world
+                .spawn((
+                    Transform::from_xyz(x, y, 0.).with_scale(Vec3::new(10., 10., 1.)),
+                    Collider,
+                    CollisionLayers::new(layer, LayerMask::ALL),
+                ))
+                .id()
+        };
+        [
+            spawn(0., 0., LayerMask::ENEMY),
+            spawn(100., 0., LayerMask::ENEMY),
+            spawn(200., 0., LayerMask::ENEMY),
+            spawn(100., 100., LayerMask::PICKUP),
+        ]
+    }
+
+    fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+        entities.sort();
+        entities
+    }
+
+    #[test]
+    fn rays_hit_colliders_in_order() {
+        let mut world = World::new();
+        let [first, second, third, _] = spawn_colliders(&mut world);
+        let origin = Vec2::new(-100., 0.);
+
+        let hit = world
+            .run_system_once(move |q: SpatialQuery| {
+                q.cast_ray(origin, Dir2::X, 1000., LayerMask::ALL)
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!((hit.entity, hit.distance), (first, 90.));
+        assert_eq!(hit.point, Vec2::new(-10., 0.));
+
+        let hits = world
+            .run_system_once(move |q: SpatialQuery| {
+                q.cast_ray_all(origin, Dir2::X, 1000., LayerMask::ENEMY)
+            })
+            .unwrap();
+        let entities: Vec<_> = hits.iter().map(|hit| hit.entity).collect();
+        assert_eq!(entities, [first, second, third]);
+
+        // the second enemy starts 190 units away
+        let hits = world
+            .run_system_once(move |q: SpatialQuery| {
+                q.cast_ray_all(origin, Dir2::X, 150., LayerMask::ENEMY)
+            })
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn points_and_areas_respect_layers() {
+        let mut world = World::new();
+        let [first, second, third, pickup] = spawn_colliders(&mut world);
+
+        let at_point = |world: &mut World, point, filter| {
+            world
+                .run_system_once(move |q: SpatialQuery| q.point_intersections(point, filter))
+                .unwrap()
+        };
+        assert_eq!(
+            at_point(&mut world, Vec2::new(105., 5.), LayerMask::ALL),
+            [second]
+        );
+        assert_eq!(
+            at_point(&mut world, Vec2::new(100., 100.), LayerMask::ALL),
+            [pickup]
+        );
+        assert!(at_point(&mut world, Vec2::new(100., 100.), LayerMask::ENEMY).is_empty());
+
+        let in_circle = world
+            .run_system_once(|q: SpatialQuery| {
+                q.circle_intersections(Vec2::ZERO, 95., LayerMask::ENEMY)
+            })
+            .unwrap();
+        assert_eq!(sorted(in_circle), sorted(vec![first, second]));
+
+        let in_aabb = world
+            .run_system_once(|q: SpatialQuery| {
+                let aabb = Aabb2d::new(Vec2::new(150., 0.), Vec2::new(45., 100.));
+                q.aabb_intersections(aabb, LayerMask::ALL)
+            })
+            .unwrap();
+        assert_eq!(sorted(in_aabb), sorted(vec![second, third, pickup]));
+    }
+
+    #[test]
+    fn line_of_sight_is_blocked_by_filtered_colliders() {
+        let mut world = World::new();
+        spawn_colliders(&mut world);
+
+        let line_of_sight = |world: &mut World, to, filter| {
+            world
+                .run_system_once(move |q: SpatialQuery| {
+                    q.line_of_sight(Vec2::new(-100., 0.), to, filter)
+                })
+                .unwrap()
+        };
+        assert!(!line_of_sight(
+            &mut world,
+            Vec2::new(50., 0.),
+            LayerMask::ENEMY
+        ));
+        assert!(line_of_sight(
+            &mut world,
+            Vec2::new(50., 0.),
+            LayerMask::PICKUP
+        ));
+        assert!(line_of_sight(
+            &mut world,
+            Vec2::new(-50., 0.),
+            LayerMask::ENEMY
+        ));
+    }
+}
@@ -0,0 +1,366 @@
+/// Steering behaviors, see https://www.red3d.com/cwr/steer/
+///
+/// Every behavior adds a force to `SteeringForce`, the sum is limited by `Steering::max_force`
+/// and written to the `Acceleration` of the entity. The regular velocity integration does the rest.
+use crate::prelude::*;
+
+use super::{Acceleration, Velocity};
+
+/// What a behavior steers towards (or away from).
+#[derive(Clone, Copy, Debug)]
+pub enum SteeringTarget {
+    Entity(Entity),
+    Point(Vec2),
+}
+
+/// Limits of an entity using steering behaviors.
+#[derive(Component, Clone, Copy)]
+#[require(SteeringForce, Acceleration)]
+pub struct Steering {
+    pub max_speed: f32,
+    pub max_force: f32,
+}
+
+impl Steering {
+    pub fn new(max_speed: f32, max_force: f32) -> Self {
+        Self {
+            max_speed,
+            max_force,
+        }
+    }
+
+    /// Force needed to change `velocity` into `desired`.
+    fn towards(&self, desired: Vec2, velocity: Vec2) -> Vec2 {
+        desired.clamp_length_max(self.max_speed) - velocity
+    }
+}
+
+/// Accumulated force of all behaviors in the current tick.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct SteeringForce(pub Vec2);
+
+/// Move towards the target at full speed.
+#[derive(Component)]
+#[require(Steering(|| Steering::new(300., 600.)))]
+pub struct Seek(pub SteeringTarget);
+
+/// Move away from the target at full speed.
+#[derive(Component)]
+#[require(Steering(|| Steering::new(300., 600.)))]
+pub struct Flee(pub SteeringTarget);
+
+/// Move towards the target and slow down inside `slowing_radius`.
+#[derive(Component)]
+#[require(Steering(|| Steering::new(300., 600.)))]
+pub struct Arrive {
+    pub target: SteeringTarget,
+    pub slowing_radius: f32,
+}
+
+/// Random but smooth wandering.
+/// A point on a circle of `radius` in front of the entity is moved by at most `jitter` radians per second.
+#[derive(Component)]
+#[require(Steering(|| Steering::new(300., 600.)))]
+pub struct Wander {
+    pub radius: f32,
+    pub distance: f32,
+    pub jitter: f32,
+    angle: f32,
+}
+
+impl Wander {
+    pub fn new(radius: f32, distance: f32, jitter: f32) -> Self {
+        Self {
+            radius,
+            distance,
+            jitter,
+            angle: 0.,
+        }
+    }
+}
+
+/// Circle around a center in counter-clockwise direction.
+#[derive(Component)]
+#[require(Steering(|| Steering::new(300., 600.)))]
+pub struct Orbit {
+    pub center: SteeringTarget,
+    pub radius: f32,
+}
+
+/// Turns the velocity towards the target by at most `turn_rate` radians per second, keeping the speed.
+/// Meant for missiles, which should not be able to stop and turn around.
+#[derive(Component)]
+#[require(Velocity)]
+pub struct Homing {
+    pub target: SteeringTarget,
+    pub turn_rate: f32,
+}
+
+/// All steering systems, modifiers of the resulting `Acceleration` run after it.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SteeringSet;
+
+pub struct SteeringPlugin;
+
+impl Plugin for SteeringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                reset_steering,
+                (seek, flee, arrive, wander, orbit),
+                apply_steering,
+                (homing, face_heading).chain(),
+            )
+                .chain()
+                .in_set(SteeringSet)
+                .in_set(GameSet::Input),
+        );
+    }
+}
+
+/// Position of the target, `None` if the target entity is gone.
+fn resolve(target: SteeringTarget, transforms: &Query<&Transform>) -> Option<Vec2> {
+    match target {
+        SteeringTarget::Point(point) => Some(point),
+        SteeringTarget::Entity(entity) => transforms
+            .get(entity)
+            .ok()
+            .map(|transform| transform.translation.truncate()),
+    }
+}
+
+fn reset_steering(mut query: Query<&mut SteeringForce>) {
+    for mut force in &mut query {
+        force.0 = Vec2::ZERO;
+    }
+}
+
+fn seek(
+    mut query: Query<(Entity, &Seek, &Steering, &Velocity, &mut SteeringForce)>,
+    transforms: Query<&Transform>,
+) {
+    for (entity, seek, steering, velocity, mut force) in &mut query {
+        let (Ok(transform), Some(target)) = (transforms.get(entity), resolve(seek.0, &transforms))
+        else {
+            continue;
+        };
+        let desired = (target - transform.translation.truncate()).normalize_or_zero();
+        force.0 += steering.towards(desired * steering.max_speed, velocity.0);
+    }
+}
+
+fn flee(
+    mut query: Query<(Entity, &Flee, &Steering, &Velocity, &mut SteeringForce)>,
+    transforms: Query<&Transform>,
+) {
+    for (entity, flee, steering, velocity, mut force) in &mut query {
+        let (Ok(transform), Some(target)) = (transforms.get(entity), resolve(flee.0, &transforms))
+        else {
+            continue;
+        };
+        let desired = (transform.translation.truncate() - target).normalize_or_zero();
+        force.0 += steering.towards(desired * steering.max_speed, velocity.0);
+    }
+}
+
+fn arrive(
+    mut query: Query<(Entity, &Arrive, &Steering, &Velocity, &mut SteeringForce)>,
+    transforms: Query<&Transform>,
+) {
+    for (entity, arrive, steering, velocity, mut force) in &mut query {
+        let (Ok(transform), Some(target)) =
+            (transforms.get(entity), resolve(arrive.target, &transforms))
+        else {
+            continue;
+        };
+        let offset = target - transform.translation.truncate();
+        let distance = offset.length();
+        let speed = steering.max_speed * (distance / arrive.slowing_radius).min(1.);
+        force.0 += steering.towards(offset.normalize_or_zero() * speed, velocity.0);
+    }
+}
+
+fn wander(
+    mut query: Query<(&mut Wander, &Steering, &Velocity, &mut SteeringForce)>,
+    time: Res<Time>,
+) {
+    for (mut wander, steering, velocity, mut force) in &mut query {
+        let jitter = wander.jitter * time.delta_secs();
+        wander.angle += rand::random_range(-1.0..=1.0) * jitter;
+
+        let heading = velocity.0.try_normalize().unwrap_or(Vec2::NEG_Y);
+        let circle_center = heading * wander.distance;
+        let displacement = Vec2::from_angle(wander.angle).rotate(heading) * wander.radius;
+
+        let desired = (circle_center + displacement).normalize_or_zero();
+        force.0 += steering.towards(desired * steering.max_speed, velocity.0);
+    }
+}
+
+fn orbit(
+    mut query: Query<(Entity, &Orbit, &Steering, &Velocity, &mut SteeringForce)>,
+    transforms: Query<&Transform>,
+) {
+    for (entity, orbit, steering, velocity, mut force) in &mut query {
+        let (Ok(transform), Some(center)) =
+            (transforms.get(entity), resolve(orbit.center, &transforms))
+        else {
+            continue;
+        };
+        let offset = transform.translation.truncate() - center;
+        let Some(radial) = offset.try_normalize() else {
+            continue;
+        };
+        // move along the tangent and correct the distance to the center,
+        // the centripetal force keeps the entity from drifting outwards on the circle
+        let distance = offset.length();
+        let correction = (orbit.radius - distance) / orbit.radius;
+        let desired = (radial.perp() + radial * correction).normalize_or_zero();
+        let centripetal = -radial * velocity.length_squared() / distance;
+        force.0 += steering.towards(desired * steering.max_speed, velocity.0) + centripetal;
+    }
+}
+
+fn apply_steering(mut query: Query<(&Steering, &SteeringForce, &mut Acceleration)>) {
+    for (steering, force, mut acceleration) in &mut query {
+        acceleration.0 = force.0.clamp_length_max(steering.max_force);
+    }
+}
+
+fn homing(
+    mut query: Query<(Entity, &Homing, &mut Velocity)>,
+    transforms: Query<&Transform>,
+    time: Res<Time>,
+) {
+    for (entity, homing, mut velocity) in &mut query {
+        let (Ok(transform), Some(target)) =
+            (transforms.get(entity), resolve(homing.target, &transforms))
+        else {
+            continue;
+        };
+        let Some(to_target) = (target - transform.translation.truncate()).try_normalize() else {
+            continue;
+        };
+        let Some(heading) = velocity.0.try_normalize() else {
+            continue;
+        };
+        let max_turn = homing.turn_rate * time.delta_secs();
+        let angle = heading.angle_to(to_target).clamp(-max_turn, max_turn);
+        velocity.0 = Vec2::from_angle(angle).rotate(velocity.0);
+    }
+}
+
+/// Homing entities point where they are flying.
+fn face_heading(mut query: Query<(&Velocity, &mut Transform), With<Homing>>) {
+    for (velocity, mut transform) in &mut query {
+        if let Some(heading) = velocity.0.try_normalize() {
+            transform.rotation = Quat::from_rotation_z(Vec2::Y.angle_to(heading));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// Runs the steering behaviors and integrates the velocity for `ticks` ticks of `dt` seconds.
+    fn simulate(world: &mut World, ticks: usize, dt: f32) {
+        for _ in 0..ticks {
+            world.run_system_once(reset_steering).unwrap();
+            world.run_system_once(flee).unwrap();
+            world.run_system_once(arrive).unwrap();
+            world.run_system_once(orbit).unwrap();
+            world.run_system_once(apply_steering).unwrap();
+            let mut query = world.query::<(&Acceleration, &mut Velocity, &mut Transform)>();
+            for (acceleration, mut velocity, mut transform) in query.iter_mut(world) {
+                velocity.0 += acceleration.0 * dt;
+                transform.translation += (velocity.0 * dt).extend(0.);
+            }
+        }
+    }
+
+    fn position(world: &World, entity: Entity) -> Vec2 {
+        world
+            .get::<Transform>(entity)
+            .unwrap()
+            .translation
+            .truncate()
+    }
+
+    #[test]
+    fn steering_towards_is_limited_by_max_speed() {
+        let steering = Steering::new(100., 50.);
+        let force = steering.towards(Vec2::new(0., 500.), Vec2::ZERO);
+        assert_eq!(force, Vec2::new(0., 100.));
+    }
+
+    #[test]
+    fn steering_towards_cancels_current_velocity() {
+        let steering = Steering::new(100., 50.);
+        let force = steering.towards(Vec2::new(100., 0.), Vec2::new(100., 0.));
+        assert_eq!(force, Vec2::ZERO);
+    }
+
+    #[test]
+    fn flee_steers_away_from_the_target() {
+        let mut world = World::new();
+        let entity = world
+            .spawn((
+                Transform::default(),
+                Flee(SteeringTarget::Point(Vec2::new(10., 0.))),
+            ))
+            .id();
+        simulate(&mut world, 1, 0.1);
+        assert_eq!(
+            world.get::<Acceleration>(entity).unwrap().0,
+            Vec2::new(-300., 0.)
+        );
+    }
+
+    #[test]
+    fn arrive_slows_down_inside_slowing_radius() {
+        let mut world = World::new();
+        let arrive = |x| {
+            (
+                Transform::from_xyz(x, 0., 0.),
+                Velocity(Vec2::new(300., 0.)),
+                Arrive {
+                    target: SteeringTarget::Point(Vec2::new(500., 0.)),
+                    slowing_radius: 200.,
+                },
+            )
+        };
+        let outside = world.spawn(arrive(0.)).id();
+        let inside = world.spawn(arrive(400.)).id();
+        simulate(&mut world, 1, 0.01);
+        // already at full speed outside, braking inside
+        assert_eq!(world.get::<Acceleration>(outside).unwrap().0, Vec2::ZERO);
+        assert!(world.get::<Acceleration>(inside).unwrap().x < 0.);
+
+        // and comes to a stop at the target
+        simulate(&mut world, 2000, 0.01);
+        assert!(position(&world, inside).distance(Vec2::new(500., 0.)) < 5.);
+        assert!(world.get::<Velocity>(inside).unwrap().length() < 5.);
+    }
+
+    #[test]
+    fn orbit_converges_to_radius() {
+        let mut world = World::new();
+        let center = Vec2::new(100., 100.);
+        let entity = world
+            .spawn((
+                Transform::from_translation((center + Vec2::new(50., 0.)).extend(0.)),
+                Orbit {
+                    center: SteeringTarget::Point(center),
+                    radius: 200.,
+                },
+            ))
+            .id();
+        simulate(&mut world, 1000, 0.01);
+        let distance = position(&world, entity).distance(center);
+        assert!((distance - 200.).abs() < 5., "{distance}");
+    }
+}
@@ -0,0 +1,116 @@
+/// Recycling of entities that are spawned and despawned at high rates, e.g. projectiles.
+///
+/// Instead of despawning, a released entity is hidden and stripped of the components
+/// that make it take part in the game. The next spawn of the same kind reuses it.
+use std::collections::HashSet;
+use std::marker::PhantomData;
+
+use bevy::ecs::system::{EntityCommands, SystemParam};
+
+use crate::physics::{Collider, Interpolated, Velocity};
+use crate::prelude::*;
+
+/// A kind of pooled entity, identified by its marker component.
+pub trait Poolable: Component + Sized {
+    /// Removes everything that makes a released entity act in the game.
+    /// Whatever is removed here has to be part of the bundle passed to `PoolCommands::spawn`.
+    fn strip(entity: &mut EntityCommands) {
+        entity.remove::<(Self, Collider, Velocity, Interpolated)>();
+    }
+}
+
+/// Free and active entities of one kind.
+#[derive(Resource)]
+pub struct Pool<T: Poolable> {
+    free: Vec<Entity>,
+    /// Released this tick, they become free in the next one so no command touches them twice in a tick.
+    released: Vec<Entity>,
+    active: HashSet<Entity>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Poolable> Default for Pool<T> {
+    fn default() -> Self {
+        Pool {
+            free: vec![],
+            released: vec![],
+            active: HashSet::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Poolable> Pool<T> {
+    pub fn active(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn free(&self) -> usize {
+        self.free.len() + self.released.len()
+    }
+
+    pub fn recycle(&mut self) {
+        self.free.append(&mut self.released);
+    }
+}
+
+/// Spawns and releases pooled entities, use it like `Commands::spawn` and `despawn`.
+#[derive(SystemParam)]
+pub struct PoolCommands<'w, 's, T: Poolable> {
+    commands: Commands<'w, 's>,
+    pool: ResMut<'w, Pool<T>>,
+}
+
+impl<T: Poolable> PoolCommands<'_, '_, T> {
+    /// Spawns `bundle` on a free entity or a new one if the pool is empty.
+    pub fn spawn(&mut self, bundle: impl Bundle) -> Entity {
+        let entity = match self.pool.free.pop() {
+            Some(entity) => {
+                // shown again unless the bundle brings its own visibility
+                self.commands
+                    .entity(entity)
+                    .insert(Visibility::Inherited)
+                    .insert(bundle);
+                entity
+            }
+            None => self.commands.spawn(bundle).id(),
+        };
+        self.pool.active.insert(entity);
+        entity
+    }
+
+    /// Commands for an active entity, e.g. to insert optional components after `spawn`.
+    pub fn entity(&mut self, entity: Entity) -> EntityCommands<'_> {
+        self.commands.entity(entity)
+    }
+
+    /// Returns an entity to the pool, releasing an entity twice has no effect.
+    pub fn release(&mut self, entity: Entity) {
+        if !self.pool.active.remove(&entity) {
+            return;
+        }
+        let mut entity_commands = self.commands.entity(entity);
+        T::strip(&mut entity_commands);
+        entity_commands.insert(Visibility::Hidden);
+        self.pool.released.push(entity);
+    }
+}
+
+pub struct PoolPlugin<T: Poolable>(PhantomData<T>);
+
+impl<T: Poolable> Default for PoolPlugin<T> {
+    fn default() -> Self {
+        PoolPlugin(PhantomData)
+    }
+}
+
+impl<T: Poolable> Plugin for PoolPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Pool<T>>()
+            .add_systems(FixedFirst, recycle::<T>);
+    }
+}
+
+fn recycle<T: Poolable>(mut pool: ResMut<Pool<T>>) {
+    pool.recycle();
+}
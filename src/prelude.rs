@@ -3,5 +3,6 @@ pub(crate) use bevy::prelude::*;
 pub(crate) use crate::constants;
 pub(crate) use crate::enemy::*;
 pub(crate) use crate::physics;
+pub(crate) use crate::schedule::GameSet;
 pub(crate) use crate::shared::*;
 pub(crate) use crate::world::*;
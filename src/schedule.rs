@@ -0,0 +1,35 @@
+use crate::prelude::*;
+
+/// The ordered stages of a single gameplay tick.
+/// All gameplay runs in `FixedUpdate`, the sets below are chained in this order.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameSet {
+    /// Player input and AI intent, i.e. everything that decides where things want to go.
+    Input,
+    /// Integrates velocities into transforms.
+    Movement,
+    /// Collision detection, sends `CollisionEvent`s.
+    Collision,
+    /// Turns collisions into gameplay events, e.g. hits and deaths.
+    Resolve,
+    /// Reacts to deaths (score, debris) and despawns what is gone.
+    Cleanup,
+}
+
+pub struct SchedulePlugin;
+
+impl Plugin for SchedulePlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            FixedUpdate,
+            (
+                GameSet::Input,
+                GameSet::Movement,
+                GameSet::Collision,
+                GameSet::Resolve,
+                GameSet::Cleanup,
+            )
+                .chain(),
+        );
+    }
+}
@@ -0,0 +1,81 @@
+/// User settings, loaded from `SETTINGS_PATH` at startup and saved whenever they change.
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::AudioSettings;
+use crate::graphics::camera::CameraSettings;
+use crate::graphics::display::DisplaySettings;
+use crate::graphics::post_process::PostProcessSettings;
+use crate::input::InputSettings;
+use crate::prelude::*;
+
+const SETTINGS_PATH: &str = "settings.ron";
+
+/// Missing entries of a settings file fall back to their defaults,
+/// so older files keep working when settings are added.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub display: DisplaySettings,
+    pub post_process: PostProcessSettings,
+    pub camera: CameraSettings,
+    pub audio: AudioSettings,
+    pub input: InputSettings,
+}
+
+impl Settings {
+    pub fn load() -> Settings {
+        Settings::load_from(SETTINGS_PATH)
+    }
+
+    fn load_from(path: &str) -> Settings {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Settings::default();
+        };
+        ron::from_str(&content).unwrap_or_else(|err| {
+            warn!("Ignoring invalid settings in {path}: {err}");
+            Settings::default()
+        })
+    }
+
+    fn save(&self, path: &str) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|content| fs::write(path, content).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            warn!("Could not save settings to {path}: {err}");
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // loaded right away, so other plugins can read the settings while they are built
+        app.insert_resource(Settings::load()).add_systems(
+            Update,
+            save.run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+        );
+    }
+}
+
+fn save(settings: Res<Settings>) {
+    settings.save(SETTINGS_PATH);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_entries_use_defaults() {
+        let settings: Settings = ron::from_str("(post_process: (crt: true))").unwrap();
+        assert!(settings.post_process.crt);
+        assert_eq!(settings.camera, CameraSettings::default());
+
+        let content = ron::to_string(&settings).unwrap();
+        assert_eq!(ron::from_str::<Settings>(&content).unwrap(), settings);
+    }
+}
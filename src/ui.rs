@@ -1,19 +1,62 @@
 use crate::prelude::constants::*;
+use std::collections::HashMap;
+
 use crate::prelude::*;
+use crate::world::bomb::Bombs;
+use crate::world::combat::DamageCause;
+use crate::world::equipment::Weapon;
+use crate::world::player::Player;
+use crate::world::power_up::PowerUps;
+use options::OptionsPlugin;
+
+pub mod options;
 
 #[derive(Resource, Deref, DerefMut)]
 pub struct Score(pub i32);
 
+/// Kills by the projectiles or beam of each weapon, by weapon name.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct WeaponKills(HashMap<&'static str, u32>);
+
 #[derive(Component)]
 pub struct ScoreboardUi;
 
+#[derive(Component)]
+pub struct WeaponUi;
+
+#[derive(Component)]
+pub struct BombsUi;
+
+/// Active power-ups with their remaining seconds, one per line.
+#[derive(Component)]
+pub struct PowerUpUi;
+
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(Score(0))
-            .add_systems(Startup, (init_scoreboard,))
-            .add_systems(Update, (update_scoreboard, on_enemy_died_score));
+        app.add_plugins(OptionsPlugin)
+            .insert_resource(Score(0))
+            .init_resource::<WeaponKills>()
+            .add_systems(
+                Startup,
+                (
+                    init_scoreboard,
+                    init_weapon_display,
+                    init_bombs_display,
+                    init_power_up_display,
+                ),
+            )
+            .add_systems(FixedUpdate, on_enemy_died_score.in_set(GameSet::Cleanup))
+            .add_systems(
+                Update,
+                (
+                    update_scoreboard,
+                    update_weapon_display,
+                    update_bombs_display,
+                    update_power_up_display,
+                ),
+            );
     }
 }
 
@@ -44,6 +87,119 @@ fn init_scoreboard(mut commands: Commands) {
         ));
 }
 
+fn init_weapon_display(mut commands: Commands) {
+    commands
+        .spawn((
+            Text::new("Weapon: "),
+            TextFont {
+                font_size: SCOREBOARD_FONT_SIZE,
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+            WeaponUi,
+            Node {
+                position_type: PositionType::Absolute,
+                top: SCOREBOARD_TEXT_PADDING,
+                right: SCOREBOARD_TEXT_PADDING,
+                ..default()
+            },
+        ))
+        .with_child((
+            TextSpan::default(),
+            TextFont {
+                font_size: SCOREBOARD_FONT_SIZE,
+                ..default()
+            },
+            TextColor(SCORE_COLOR),
+        ));
+}
+
+fn init_bombs_display(mut commands: Commands) {
+    commands
+        .spawn((
+            Text::new("Bombs: "),
+            TextFont {
+                font_size: SCOREBOARD_FONT_SIZE,
+                ..default()
+            },
+            TextColor(TEXT_COLOR),
+            BombsUi,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: SCOREBOARD_TEXT_PADDING,
+                left: SCOREBOARD_TEXT_PADDING,
+                ..default()
+            },
+        ))
+        .with_child((
+            TextSpan::default(),
+            TextFont {
+                font_size: SCOREBOARD_FONT_SIZE,
+                ..default()
+            },
+            TextColor(SCORE_COLOR),
+        ));
+}
+
+fn init_power_up_display(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: SCOREBOARD_FONT_SIZE,
+            ..default()
+        },
+        TextColor(SCORE_COLOR),
+        TextLayout::new_with_justify(JustifyText::Right),
+        PowerUpUi,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: SCOREBOARD_TEXT_PADDING,
+            right: SCOREBOARD_TEXT_PADDING,
+            ..default()
+        },
+    ));
+}
+
+fn update_weapon_display(
+    weapon: Single<Ref<Weapon>, With<Player>>,
+    kills: Res<WeaponKills>,
+    weapon_root: Single<Entity, (With<WeaponUi>, With<Text>)>,
+    mut writer: TextUiWriter,
+) {
+    if !weapon.is_changed() && !kills.is_changed() {
+        return;
+    }
+    let kills = kills.get(weapon.name).copied().unwrap_or(0);
+    *writer.text(*weapon_root, 1) = format!("{} ({kills} kills)", weapon.name);
+}
+
+fn update_bombs_display(
+    bombs: Single<&Bombs, (With<Player>, Changed<Bombs>)>,
+    bombs_root: Single<Entity, (With<BombsUi>, With<Text>)>,
+    mut writer: TextUiWriter,
+) {
+    *writer.text(*bombs_root, 1) = bombs.stock.to_string();
+}
+
+fn update_power_up_display(
+    power_ups: Single<&PowerUps, (With<Player>, Changed<PowerUps>)>,
+    power_up_root: Single<Entity, (With<PowerUpUi>, With<Text>)>,
+    mut writer: TextUiWriter,
+) {
+    let lines: Vec<_> = power_ups
+        .active()
+        .iter()
+        .map(|active| {
+            let secs = active.secs_left.ceil();
+            match active.stacks {
+                1 => format!("{} {secs}s", active.power_up.label()),
+                stacks => format!("{} x{stacks} {secs}s", active.power_up.label()),
+            }
+        })
+        .collect();
+    *writer.text(*power_up_root, 0) = lines.join("\n");
+}
+
 fn update_scoreboard(
     score: Res<Score>,
     score_root: Single<Entity, (With<ScoreboardUi>, With<Text>)>,
@@ -52,11 +208,23 @@ fn update_scoreboard(
     *writer.text(*score_root, 1) = score.to_string();
 }
 
-fn on_enemy_died_score(mut score: ResMut<Score>, mut ev_enemy_died: EventReader<EnemyDiedEvent>) {
+fn on_enemy_died_score(
+    mut score: ResMut<Score>,
+    mut kills: ResMut<WeaponKills>,
+    mut ev_enemy_died: EventReader<EnemyDiedEvent>,
+) {
     for event in ev_enemy_died.read() {
+        // only kills credited to a player score
+        if event.player.is_none() {
+            continue;
+        }
         match event.enemy_type {
             EnemyType::Creep => **score += 1,
             EnemyType::Standard => **score += 2,
+            EnemyType::Gunner => **score += 3,
+        }
+        if let DamageCause::Weapon(weapon) = event.cause {
+            *kills.entry(weapon).or_default() += 1;
         }
     }
 }
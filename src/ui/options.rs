@@ -0,0 +1,323 @@
+/// Options menu, opened with the pause action. The game is paused while it is open.
+use crate::graphics::display::{DisplayMode, RESOLUTIONS, Scaling};
+use crate::input::{Action, ActionState, ControlScheme};
+use crate::prelude::constants::*;
+use crate::prelude::*;
+use crate::settings::Settings;
+
+/// A setting in the menu, pressing it switches to the next value.
+/// Pressing a binding waits for the key to bind, see `Rebinding`.
+#[derive(Component, Clone, Copy, Debug)]
+enum OptionEntry {
+    Resolution,
+    DisplayMode,
+    Vsync,
+    Scaling,
+    Bloom,
+    Crt,
+    ChromaticAberration,
+    ScreenShake,
+    HitStop,
+    ZoomPulses,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Rumble,
+    Controls,
+    /// An action and the value the key contributes, see `Action::BINDINGS`
+    Binding(Action, f32),
+}
+
+impl OptionEntry {
+    const SETTINGS: [OptionEntry; 15] = [
+        OptionEntry::Resolution,
+        OptionEntry::DisplayMode,
+        OptionEntry::Vsync,
+        OptionEntry::Scaling,
+        OptionEntry::Bloom,
+        OptionEntry::Crt,
+        OptionEntry::ChromaticAberration,
+        OptionEntry::ScreenShake,
+        OptionEntry::HitStop,
+        OptionEntry::ZoomPulses,
+        OptionEntry::MasterVolume,
+        OptionEntry::MusicVolume,
+        OptionEntry::SfxVolume,
+        OptionEntry::Rumble,
+        OptionEntry::Controls,
+    ];
+
+    fn all() -> impl Iterator<Item = OptionEntry> {
+        OptionEntry::SETTINGS
+            .into_iter()
+            .chain(Action::BINDINGS.map(|(action, value)| OptionEntry::Binding(action, value)))
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            OptionEntry::Resolution => "Resolution",
+            OptionEntry::DisplayMode => "Window mode",
+            OptionEntry::Vsync => "VSync",
+            OptionEntry::Scaling => "Scaling",
+            OptionEntry::Bloom => "Bloom",
+            OptionEntry::Crt => "CRT filter",
+            OptionEntry::ChromaticAberration => "Chromatic aberration",
+            OptionEntry::ScreenShake => "Screen shake",
+            OptionEntry::HitStop => "Hit-stop",
+            OptionEntry::ZoomPulses => "Zoom pulses",
+            OptionEntry::MasterVolume => "Master volume",
+            OptionEntry::MusicVolume => "Music volume",
+            OptionEntry::SfxVolume => "Effects volume",
+            OptionEntry::Rumble => "Gamepad rumble",
+            OptionEntry::Controls => "Controls",
+            OptionEntry::Binding(action, value) => action.label(*value),
+        }
+    }
+
+    fn value(&self, settings: &Settings, rebinding: &Rebinding) -> String {
+        let on_off = |on: bool| if on { "on" } else { "off" }.to_string();
+        let percent = |volume: f32| format!("{:.0}%", volume * 100.);
+        let display = &settings.display;
+        match self {
+            OptionEntry::Resolution => format!("{}x{}", display.resolution.x, display.resolution.y),
+            OptionEntry::DisplayMode => format!("{:?}", display.mode),
+            OptionEntry::Vsync => on_off(display.vsync),
+            OptionEntry::Scaling => format!("{:?}", display.scaling),
+            OptionEntry::Bloom => on_off(settings.post_process.bloom),
+            OptionEntry::Crt => on_off(settings.post_process.crt),
+            OptionEntry::ChromaticAberration => on_off(settings.post_process.chromatic_aberration),
+            OptionEntry::ScreenShake => on_off(settings.camera.shake > 0.),
+            OptionEntry::HitStop => on_off(settings.camera.hit_stop),
+            OptionEntry::ZoomPulses => on_off(settings.camera.zoom_pulses),
+            OptionEntry::MasterVolume => percent(settings.audio.master),
+            OptionEntry::MusicVolume => percent(settings.audio.music),
+            OptionEntry::SfxVolume => percent(settings.audio.sfx),
+            OptionEntry::Rumble => on_off(settings.input.rumble),
+            OptionEntry::Controls => format!("{:?}", settings.input.scheme),
+            OptionEntry::Binding(action, value) if rebinding.0 == Some((*action, *value)) => {
+                "press a key".into()
+            }
+            OptionEntry::Binding(action, value) => settings
+                .input
+                .keys(*action)
+                .iter()
+                .filter(|(_, bound)| bound == value)
+                .map(|(key, _)| format!("{key:?}"))
+                .collect::<Vec<_>>()
+                .join(" / "),
+        }
+    }
+
+    fn next(&self, settings: &mut Settings) {
+        // volumes go up in steps of 10%, wrapping around to silent
+        let louder = |volume: f32| ((volume * 10.).round() + 1.) % 11. / 10.;
+        let display = &mut settings.display;
+        match self {
+            OptionEntry::Resolution => {
+                let current = RESOLUTIONS.iter().position(|r| *r == display.resolution);
+                display.resolution =
+                    RESOLUTIONS[current.map_or(0, |i| (i + 1) % RESOLUTIONS.len())];
+            }
+            OptionEntry::DisplayMode => {
+                display.mode = match display.mode {
+                    DisplayMode::Windowed => DisplayMode::Borderless,
+                    DisplayMode::Borderless => DisplayMode::Fullscreen,
+                    DisplayMode::Fullscreen => DisplayMode::Windowed,
+                }
+            }
+            OptionEntry::Vsync => display.vsync = !display.vsync,
+            OptionEntry::Scaling => {
+                display.scaling = match display.scaling {
+                    Scaling::Letterbox => Scaling::Stretch,
+                    Scaling::Stretch => Scaling::Letterbox,
+                }
+            }
+            OptionEntry::Bloom => settings.post_process.bloom = !settings.post_process.bloom,
+            OptionEntry::Crt => settings.post_process.crt = !settings.post_process.crt,
+            OptionEntry::ChromaticAberration => {
+                settings.post_process.chromatic_aberration =
+                    !settings.post_process.chromatic_aberration
+            }
+            OptionEntry::ScreenShake => {
+                settings.camera.shake = if settings.camera.shake > 0. { 0. } else { 1. }
+            }
+            OptionEntry::HitStop => settings.camera.hit_stop = !settings.camera.hit_stop,
+            OptionEntry::ZoomPulses => settings.camera.zoom_pulses = !settings.camera.zoom_pulses,
+            OptionEntry::MasterVolume => settings.audio.master = louder(settings.audio.master),
+            OptionEntry::MusicVolume => settings.audio.music = louder(settings.audio.music),
+            OptionEntry::SfxVolume => settings.audio.sfx = louder(settings.audio.sfx),
+            OptionEntry::Rumble => settings.input.rumble = !settings.input.rumble,
+            OptionEntry::Controls => {
+                settings.input.scheme = match settings.input.scheme {
+                    ControlScheme::Classic => ControlScheme::TwinStick,
+                    ControlScheme::TwinStick => ControlScheme::Classic,
+                }
+            }
+            // bound by `capture_binding`
+            OptionEntry::Binding(..) => {}
+        }
+    }
+}
+
+#[derive(Component)]
+struct OptionsMenu;
+
+/// The action and value waiting for its new key, the next key pressed is bound to it.
+#[derive(Resource, Default)]
+struct Rebinding(Option<(Action, f32)>);
+
+pub struct OptionsPlugin;
+
+impl Plugin for OptionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_systems(Startup, (init_options_menu,))
+            .add_systems(
+                Update,
+                (
+                    // the key ending a rebinding must not close the menu
+                    toggle_options_menu.run_if(|rebinding: Res<Rebinding>| rebinding.0.is_none()),
+                    on_option_pressed,
+                    capture_binding,
+                    update_option_labels
+                        .run_if(resource_changed::<Settings>.or(resource_changed::<Rebinding>)),
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn init_options_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("OptionsMenu"),
+            OptionsMenu,
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: OPTIONS_BUTTON_GAP,
+                ..default()
+            },
+            BackgroundColor(OPTIONS_BACKGROUND_COLOR),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Options"),
+                TextFont {
+                    font_size: SCOREBOARD_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+            ));
+            // laid out in columns, so all entries fit the smallest resolution
+            parent
+                .spawn(Node {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::auto(OPTIONS_COLUMNS),
+                    row_gap: OPTIONS_BUTTON_GAP,
+                    column_gap: OPTIONS_BUTTON_GAP,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for option in OptionEntry::all() {
+                        parent
+                            .spawn((
+                                option,
+                                Button,
+                                Node {
+                                    width: OPTIONS_BUTTON_WIDTH,
+                                    padding: UiRect::all(SCOREBOARD_TEXT_PADDING),
+                                    justify_content: JustifyContent::Center,
+                                    ..default()
+                                },
+                                BackgroundColor(OPTIONS_BUTTON_COLOR),
+                            ))
+                            .with_child((
+                                Text::new(option.label()),
+                                TextFont {
+                                    font_size: OPTIONS_FONT_SIZE,
+                                    ..default()
+                                },
+                                TextColor(SCORE_COLOR),
+                            ));
+                    }
+                });
+        });
+}
+
+fn toggle_options_menu(
+    actions: Res<ActionState>,
+    menu: Single<&mut Node, With<OptionsMenu>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+
+    let mut menu = menu.into_inner();
+    if menu.display == Display::None {
+        menu.display = Display::Flex;
+        time.pause();
+    } else {
+        menu.display = Display::None;
+        time.unpause();
+    }
+}
+
+fn on_option_pressed(
+    mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut buttons: Query<(&Interaction, &OptionEntry, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, option, mut color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => {
+                match option {
+                    OptionEntry::Binding(action, value) => rebinding.0 = Some((*action, *value)),
+                    _ => option.next(&mut settings),
+                }
+                color.0 = OPTIONS_BUTTON_PRESSED_COLOR;
+            }
+            Interaction::Hovered => color.0 = OPTIONS_BUTTON_HOVERED_COLOR,
+            Interaction::None => color.0 = OPTIONS_BUTTON_COLOR,
+        }
+    }
+}
+
+fn capture_binding(
+    mut settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    let Some((action, value)) = rebinding.0 else {
+        return;
+    };
+    if let Some(key) = keyboard_input.get_just_pressed().next() {
+        settings.input.rebind(action, value, *key);
+        rebinding.0 = None;
+    }
+}
+
+fn update_option_labels(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    buttons: Query<(&OptionEntry, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (option, children) in &buttons {
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.0 = format!(
+                    "{}: {}",
+                    option.label(),
+                    option.value(&settings, &rebinding)
+                );
+            }
+        }
+    }
+}
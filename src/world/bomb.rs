@@ -0,0 +1,220 @@
+/// Bombs clear the screen: enemy projectiles vanish and every enemy takes heavy damage.
+use bevy::math::bounding::Aabb2d;
+
+use crate::game_assets::GameAssets;
+use crate::input::{Action, ActionState};
+use crate::physics::spatial_query::SpatialQuery;
+use crate::pool::PoolCommands;
+use crate::prelude::constants::*;
+use crate::prelude::physics::*;
+use crate::prelude::*;
+
+use super::combat::{Damage, DamageCause, DamageKind};
+use super::pickup::{PickupCollectedEvent, PickupKind};
+use super::player::{Invulnerable, Player};
+use super::status::StatusEffect;
+
+/// Bombs the player has left.
+#[derive(Component)]
+pub struct Bombs {
+    pub stock: u32,
+    last_used: Option<f32>,
+}
+
+impl Default for Bombs {
+    fn default() -> Self {
+        Bombs {
+            stock: BOMB_START_STOCK,
+            last_used: None,
+        }
+    }
+}
+
+impl Bombs {
+    /// Takes a bomb from the stock at time `now`, if there is one. The last bomb has to
+    /// wear off first.
+    fn take(&mut self, now: f32) -> bool {
+        let active = self
+            .last_used
+            .is_some_and(|last_used| now - last_used < BOMB_INVULNERABILITY_SECS);
+        if self.stock == 0 || active {
+            return false;
+        }
+        self.stock -= 1;
+        self.last_used = Some(now);
+        true
+    }
+
+    fn add(&mut self) {
+        self.stock = (self.stock + 1).min(BOMB_MAX_STOCK);
+    }
+}
+
+#[derive(Event)]
+pub struct BombDetonatedEvent {
+    pub position: Vec2,
+}
+
+/// Ring spreading from a detonation until it covers the screen.
+#[derive(Component)]
+struct Shockwave {
+    age: f32,
+}
+
+pub struct BombPlugin;
+
+impl Plugin for BombPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BombDetonatedEvent>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    (detonate, blast).chain().in_set(GameSet::Input),
+                    (clear_projectiles, on_pickup_collected).in_set(GameSet::Cleanup),
+                ),
+            )
+            .add_systems(Update, (spawn_shockwave, spread_shockwave));
+    }
+}
+
+fn detonate(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    time: Res<Time>,
+    player_query: Single<(Entity, &mut Bombs, &Transform), With<Player>>,
+    mut detonated: EventWriter<BombDetonatedEvent>,
+) {
+    if !actions.fixed_just_pressed(Action::Bomb) {
+        return;
+    }
+
+    let (player, mut bombs, transform) = player_query.into_inner();
+    if !bombs.take(time.elapsed_secs()) {
+        return;
+    }
+
+    let position = transform.translation.truncate();
+    commands
+        .entity(player)
+        .insert(Invulnerable(BOMB_INVULNERABILITY_SECS));
+    detonated.send(BombDetonatedEvent { position });
+}
+
+/// Every enemy the shockwave reaches is hit.
+fn blast(
+    mut events: EventReader<BombDetonatedEvent>,
+    player_query: Single<Entity, With<Player>>,
+    enemy_query: Query<&Transform, With<Enemy>>,
+    spatial_query: SpatialQuery,
+    mut damage_events: EventWriter<Damage>,
+) {
+    let player = player_query.into_inner();
+    for event in events.read() {
+        let enemies =
+            spatial_query.circle_intersections(event.position, SHOCKWAVE_RADIUS, LayerMask::ENEMY);
+        for enemy in enemies {
+            let Ok(transform) = enemy_query.get(enemy) else {
+                continue;
+            };
+            damage_events.send(Damage {
+                target: enemy,
+                amount: BOMB_DAMAGE,
+                kind: DamageKind::Explosive,
+                cause: DamageCause::Bomb,
+                source: Some(player),
+                position: transform.translation.truncate(),
+                // survivors are stunned
+                status: Some(StatusEffect::Stun),
+            });
+        }
+    }
+}
+
+/// Enemy projectiles on screen vanish.
+fn clear_projectiles(
+    mut projectiles: PoolCommands<EnemyProjectile>,
+    mut events: EventReader<BombDetonatedEvent>,
+    spatial_query: SpatialQuery,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    let screen = Aabb2d::new(Vec2::ZERO, Vec2::new(RIGHT, TOP));
+    for entity in spatial_query.aabb_intersections(screen, LayerMask::ENEMY_PROJECTILE) {
+        projectiles.release(entity);
+    }
+}
+
+fn on_pickup_collected(
+    mut events: EventReader<PickupCollectedEvent>,
+    query: Single<&mut Bombs, With<Player>>,
+) {
+    let mut bombs = query.into_inner();
+    for event in events.read() {
+        if event.kind == PickupKind::Bomb {
+            bombs.add();
+        }
+    }
+}
+
+fn spawn_shockwave(
+    mut commands: Commands,
+    mut events: EventReader<BombDetonatedEvent>,
+    assets: Res<GameAssets>,
+) {
+    for event in events.read() {
+        commands.spawn((
+            Name::new("Shockwave"),
+            Shockwave { age: 0. },
+            Mesh2d(assets.ring_mesh.clone()),
+            MeshMaterial2d(assets.shockwave_material.clone()),
+            Transform {
+                translation: event.position.extend(1.0),
+                scale: Vec3::ZERO,
+                ..default()
+            },
+        ));
+    }
+}
+
+fn spread_shockwave(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Shockwave, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (entity, mut shockwave, mut transform) in &mut query {
+        shockwave.age += time.delta_secs();
+        if shockwave.age >= SHOCKWAVE_SECS {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // fast at first, slowing down towards the edges of the screen
+        let t = shockwave.age / SHOCKWAVE_SECS;
+        let radius = SHOCKWAVE_RADIUS * (1. - (1. - t).powi(3));
+        transform.scale = Vec3::new(radius, radius, 1.);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bombs_are_limited() {
+        let mut bombs = Bombs {
+            stock: 2,
+            ..default()
+        };
+        assert!(bombs.take(0.));
+        // the first one is still going off
+        assert!(!bombs.take(0.1));
+        assert!(bombs.take(BOMB_INVULNERABILITY_SECS));
+        assert!(!bombs.take(10.));
+        assert_eq!(bombs.stock, 0);
+
+        for _ in 0..BOMB_MAX_STOCK + 1 {
+            bombs.add();
+        }
+        assert_eq!(bombs.stock, BOMB_MAX_STOCK);
+    }
+}
@@ -0,0 +1,182 @@
+/// Damage of any kind to anything with `Health`: critical hits, resistances and armor are applied
+/// in one place, deaths are reported as `DeathEvent`s.
+use crate::prelude::constants::*;
+use crate::prelude::*;
+
+use super::status::StatusEffect;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DamageKind {
+    Kinetic,
+    Energy,
+    Fire,
+    Explosive,
+}
+
+/// What dealt the damage, e.g. for per-weapon stats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DamageCause {
+    /// A projectile or the beam of the weapon with the given name
+    Weapon(&'static str),
+    Bomb,
+    /// Damage over time of a status effect
+    Status(StatusEffect),
+    /// Ships ramming each other
+    Contact,
+}
+
+/// Deals `amount` damage to `target`, before criticals, resistances and armor.
+#[derive(Event, Clone, Debug)]
+pub struct Damage {
+    pub target: Entity,
+    pub amount: f32,
+    pub kind: DamageKind,
+    pub cause: DamageCause,
+    /// The entity dealing the damage, its `Critical` applies to weapon hits
+    pub source: Option<Entity>,
+    pub position: Vec2,
+    /// Inflicted on the target along with the damage
+    pub status: Option<StatusEffect>,
+}
+
+/// Damage that took effect, after criticals, resistances and armor.
+#[derive(Event, Clone, Debug)]
+pub struct DamageDealtEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub position: Vec2,
+    pub status: Option<StatusEffect>,
+    pub critical: bool,
+}
+
+/// Sent once when the health of an entity drops to zero.
+#[derive(Event, Clone, Debug)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    pub position: Vec2,
+    /// What dealt the final blow
+    pub cause: DamageCause,
+    /// Whoever dealt the final blow
+    pub source: Option<Entity>,
+    /// Damage beyond what was needed to kill
+    pub overkill: f32,
+}
+
+/// Subtracted from every hit, a hit deals at least `ARMOR_MIN_DAMAGE_FRACTION` of its damage.
+#[derive(Component, Deref)]
+pub struct Armor(pub f32);
+
+/// Fraction of the damage of each kind that is ignored, negative values are weaknesses.
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct Resistances {
+    pub kinetic: f32,
+    pub energy: f32,
+    pub fire: f32,
+    pub explosive: f32,
+}
+
+impl Resistances {
+    pub fn get(&self, kind: DamageKind) -> f32 {
+        match kind {
+            DamageKind::Kinetic => self.kinetic,
+            DamageKind::Energy => self.energy,
+            DamageKind::Fire => self.fire,
+            DamageKind::Explosive => self.explosive,
+        }
+    }
+}
+
+/// Chance of the damage an entity deals to be multiplied.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Critical {
+    pub chance: f32,
+    pub multiplier: f32,
+}
+
+/// Damage left of `amount` after resistances and armor.
+fn mitigate(amount: f32, kind: DamageKind, resistances: Resistances, armor: f32) -> f32 {
+    let resisted = amount * (1. - resistances.get(kind));
+    (resisted - armor).max(resisted * ARMOR_MIN_DAMAGE_FRACTION)
+}
+
+/// Damage is applied at the end of `GameSet::Resolve`, senders run before and readers after it.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CombatSet;
+
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Damage>()
+            .add_event::<DamageDealtEvent>()
+            .add_event::<DeathEvent>()
+            .configure_sets(FixedUpdate, CombatSet.in_set(GameSet::Resolve))
+            .add_systems(FixedUpdate, apply_damage.in_set(CombatSet));
+    }
+}
+
+fn apply_damage(
+    mut events: EventReader<Damage>,
+    mut targets: Query<(&mut Health, Option<&Resistances>, Option<&Armor>)>,
+    sources: Query<&Critical>,
+    mut dealt: EventWriter<DamageDealtEvent>,
+    mut deaths: EventWriter<DeathEvent>,
+) {
+    for event in events.read() {
+        let Ok((mut health, resistances, armor)) = targets.get_mut(event.target) else {
+            continue;
+        };
+        // already dead, e.g. from an earlier hit in the same tick
+        if health.current <= 0. {
+            continue;
+        }
+
+        let critical = event
+            .source
+            .filter(|_| matches!(event.cause, DamageCause::Weapon(_)))
+            .and_then(|source| sources.get(source).ok())
+            .filter(|critical| rand::random_range(0.0..1.0) < critical.chance);
+        let amount = event.amount * critical.map_or(1., |critical| critical.multiplier);
+        let resistances = resistances.copied().unwrap_or_default();
+        let amount = mitigate(amount, event.kind, resistances, armor.map_or(0., |a| **a));
+
+        health.current -= amount;
+        dealt.send(DamageDealtEvent {
+            target: event.target,
+            source: event.source,
+            position: event.position,
+            status: event.status,
+            critical: critical.is_some(),
+        });
+        if health.current <= 0. {
+            deaths.send(DeathEvent {
+                entity: event.target,
+                position: event.position,
+                cause: event.cause,
+                source: event.source,
+                overkill: -health.current,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn damage_is_mitigated() {
+        let resistances = Resistances {
+            explosive: 0.5,
+            fire: -0.5,
+            ..default()
+        };
+        assert_eq!(mitigate(4., DamageKind::Explosive, resistances, 0.), 2.);
+        assert_eq!(mitigate(4., DamageKind::Fire, resistances, 1.), 5.);
+        // armor never blocks a hit completely
+        assert_eq!(
+            mitigate(1., DamageKind::Kinetic, resistances, 5.),
+            ARMOR_MIN_DAMAGE_FRACTION
+        );
+    }
+}
@@ -1,32 +1,55 @@
-use bevy::math::NormedVectorSpace;
-
+use crate::game_assets::GameAssets;
+use crate::graphics::animation::{AnimationState, Sheet, SpriteAnimation};
+use crate::graphics::effects::{EffectMaterials, MaterialEffects};
 use crate::helpers::poly_path::PolyPath;
+use crate::physics::spatial_query::SpatialQuery;
+use crate::physics::steering::{Arrive, Flee, Orbit, Seek, SteeringTarget, Wander};
+use crate::pool::{PoolCommands, PoolPlugin, Poolable};
 use crate::prelude::*;
 use crate::{constants::*, physics::*};
 
-use super::player::PlayerProjectile;
+use super::combat::{
+    Armor, CombatSet, Damage, DamageCause, DamageDealtEvent, DamageKind, DeathEvent, Resistances,
+};
+use super::player::{Player, PlayerProjectile, ProjectileHit};
+use super::status::StatusEffects;
 
 #[derive(Event)]
 pub struct EnemyDiedEvent {
     pub entity: Entity,
     pub position: Vec2,
     pub enemy_type: EnemyType,
+    /// The weapon, bomb or status effect that dealt the final blow
+    pub cause: DamageCause,
+    /// Damage beyond what was needed to kill
+    pub overkill: f32,
+    /// The player credited with the kill
+    pub player: Option<Entity>,
+}
+
+/// An enemy fired a projectile.
+#[derive(Event)]
+pub struct EnemyFiredEvent {
+    pub entity: Entity,
 }
 
+/// Damage an enemy took, see `combat::DamageDealtEvent`.
 #[derive(Event)]
 pub struct EnemyHitEvent {
     pub entity: Entity,
     pub position: Vec2,
-    pub damage: i32,
+    pub critical: bool,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum EnemyType {
     Creep,
     Standard,
+    Gunner,
 }
 
 #[derive(Component)]
+#[require(StatusEffects)]
 pub struct Enemy {
     pub enemy_type: EnemyType,
 }
@@ -37,80 +60,167 @@ impl Enemy {
     }
 }
 
+/// Fires at the player every `ENEMY_FIRE_SECS` seconds, unless frozen or stunned.
+/// Gunners hold their fire while other enemies are in the way.
+#[derive(Component)]
+pub struct EnemyGun {
+    cooldown: f32,
+}
+
+impl Default for EnemyGun {
+    fn default() -> Self {
+        EnemyGun {
+            cooldown: ENEMY_FIRE_SECS,
+        }
+    }
+}
+
+/// Hits the player like a ramming enemy, with the damage of its `ProjectileHit`.
+#[derive(Component)]
+pub struct EnemyProjectile;
+
+impl Poolable for EnemyProjectile {}
+
+/// Speed of an enemy along its `PolyPath`, before status effects.
+#[derive(Component, Deref)]
+pub struct PathSpeed(pub f32);
+
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<EnemyDiedEvent>()
+        app.add_plugins(PoolPlugin::<EnemyProjectile>::default())
+            .add_event::<EnemyDiedEvent>()
             .add_event::<EnemyHitEvent>()
-            .add_systems(Update, (on_collision, on_hit, on_enemy_died).chain())
-            .add_systems(Update, (spawn_enemies, follow_path));
+            .add_event::<EnemyFiredEvent>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    (spawn_enemies, follow_path, fire_at_player).in_set(GameSet::Input),
+                    on_collision.before(CombatSet).in_set(GameSet::Resolve),
+                    (on_damage_dealt, on_death, on_enemy_died)
+                        .chain()
+                        .after(CombatSet)
+                        .in_set(GameSet::Resolve),
+                ),
+            );
     }
 }
 
 fn on_collision(
-    mut commands: Commands,
+    mut projectiles: PoolCommands<PlayerProjectile>,
     mut colission_events: EventReader<CollisionEvent>,
-    mut enemy_hit_events: EventWriter<EnemyHitEvent>,
-    projectile_query: Query<Entity, With<PlayerProjectile>>,
+    mut damage_events: EventWriter<Damage>,
+    projectile_query: Query<&ProjectileHit, With<PlayerProjectile>>,
     enemy_query: Query<(Entity, &Transform), With<Enemy>>,
 ) {
     fn send_event(
         enemy: Entity,
         projectile: Entity,
         q: &Query<(Entity, &Transform), With<Enemy>>,
-        writer: &mut EventWriter<EnemyHitEvent>,
-        commands: &mut Commands,
+        hits: &Query<&ProjectileHit, With<PlayerProjectile>>,
+        writer: &mut EventWriter<Damage>,
+        projectiles: &mut PoolCommands<PlayerProjectile>,
     ) {
         let (enemy, transform) = q.get(enemy).unwrap();
-        writer.send(EnemyHitEvent {
-            damage: 1,
+        let hit = hits.get(projectile).unwrap();
+        writer.send(Damage {
+            target: enemy,
+            amount: hit.damage,
+            kind: hit.kind,
+            cause: hit.cause,
+            source: Some(hit.source),
             position: transform.translation.truncate(),
-            entity: enemy,
+            status: hit.status,
         });
         // TODO: Make this an extra event + cleanup?
-        commands.entity(projectile).despawn_recursive();
+        projectiles.release(projectile);
     }
 
     for event in colission_events.read() {
         let (e1, e2) = (event.entity1, event.entity2);
 
         if projectile_query.contains(e1) && enemy_query.contains(e2) {
-            send_event(e2, e1, &enemy_query, &mut enemy_hit_events, &mut commands);
+            send_event(
+                e2,
+                e1,
+                &enemy_query,
+                &projectile_query,
+                &mut damage_events,
+                &mut projectiles,
+            );
         }
 
         if projectile_query.contains(e2) && enemy_query.contains(e1) {
-            send_event(e1, e2, &enemy_query, &mut enemy_hit_events, &mut commands);
+            send_event(
+                e1,
+                e2,
+                &enemy_query,
+                &projectile_query,
+                &mut damage_events,
+                &mut projectiles,
+            );
         }
     }
 }
 
-fn on_hit(
-    mut events: EventReader<EnemyHitEvent>,
-    mut q: Query<(Entity, &mut Health), With<Enemy>>,
-    mut died: EventWriter<EnemyDiedEvent>,
+fn on_damage_dealt(
+    mut events: EventReader<DamageDealtEvent>,
+    q: Query<(), With<Enemy>>,
+    mut enemy_hit_events: EventWriter<EnemyHitEvent>,
 ) {
     for event in events.read() {
-        if q.contains(event.entity) {
-            let e = event.entity;
-            if let Ok((_, mut health)) = q.get_mut(e) {
-                if health.0 == 1 {
-                    died.send(EnemyDiedEvent {
-                        entity: e,
-                        position: event.position,
-                        enemy_type: EnemyType::Creep,
-                    });
-                } else {
-                    health.0 -= 1;
-                }
-            }
+        if q.contains(event.target) {
+            enemy_hit_events.send(EnemyHitEvent {
+                entity: event.target,
+                position: event.position,
+                critical: event.critical,
+            });
         }
     }
 }
 
-fn follow_path(mut query: Query<(&mut Velocity, &Transform, &mut PolyPath)>, time: Res<Time>) {
-    for (mut velocity, transform, mut path) in &mut query {
-        let vabs = velocity.norm();
+fn on_death(
+    mut events: EventReader<DeathEvent>,
+    q: Query<&Enemy>,
+    player_query: Query<(), With<Player>>,
+    mut died: EventWriter<EnemyDiedEvent>,
+) {
+    for event in events.read() {
+        let Ok(enemy) = q.get(event.entity) else {
+            continue;
+        };
+        died.send(EnemyDiedEvent {
+            entity: event.entity,
+            position: event.position,
+            enemy_type: enemy.enemy_type.clone(),
+            cause: event.cause,
+            overkill: event.overkill,
+            player: event.source.filter(|source| player_query.contains(*source)),
+        });
+    }
+}
+
+/// Slowed enemies step along their path slower, frozen ones stop and stunned ones drift.
+fn follow_path(
+    mut query: Query<(
+        &mut Velocity,
+        &Transform,
+        &mut PolyPath,
+        &PathSpeed,
+        &StatusEffects,
+    )>,
+    time: Res<Time>,
+) {
+    for (mut velocity, transform, mut path, speed, statuses) in &mut query {
+        let vabs = **speed * statuses.speed_factor();
+        if vabs == 0. {
+            velocity.0 = Vec2::ZERO;
+            continue;
+        }
+        if !statuses.can_act() {
+            continue;
+        }
         let dx = vabs * time.delta_secs();
         let pos = transform.translation.truncate();
         let next_pos = path.step(dx);
@@ -119,13 +229,72 @@ fn follow_path(mut query: Query<(&mut Velocity, &Transform, &mut PolyPath)>, tim
     }
 }
 
+fn fire_at_player(
+    mut projectiles: PoolCommands<EnemyProjectile>,
+    mut fired: EventWriter<EnemyFiredEvent>,
+    time: Res<Time>,
+    assets: Res<GameAssets>,
+    mut gun_query: Query<(Entity, &mut EnemyGun, &Transform, &StatusEffects)>,
+    player_query: Option<Single<&Transform, With<Player>>>,
+    spatial_query: SpatialQuery,
+) {
+    let Some(player_transform) = player_query else {
+        return;
+    };
+    let target = player_transform.translation.truncate();
+    for (entity, mut gun, transform, statuses) in &mut gun_query {
+        gun.cooldown -= time.delta_secs();
+        if gun.cooldown > 0. || !statuses.can_act() {
+            continue;
+        }
+        let Ok(direction) = Dir2::new(target - transform.translation.truncate()) else {
+            continue;
+        };
+        // the muzzle is just outside the gunner's own collider
+        let pos = transform.translation.truncate()
+            + *direction * (transform.scale.truncate().length() + 1.);
+        if !spatial_query.line_of_sight(pos, target, LayerMask::ENEMY) {
+            continue;
+        }
+        gun.cooldown = ENEMY_FIRE_SECS;
+        projectiles.spawn((
+            Name::new("EnemyProjectile"),
+            Mesh2d(assets.projectile_mesh.clone()),
+            MeshMaterial2d(assets.enemy_projectile_material.clone()),
+            Transform {
+                translation: pos.extend(0.0),
+                rotation: Quat::from_rotation_z(Vec2::Y.angle_to(*direction)),
+                ..default()
+            },
+            EnemyProjectile,
+            ProjectileHit {
+                damage: ENEMY_PROJECTILE_DAMAGE,
+                kind: DamageKind::Energy,
+                cause: DamageCause::Weapon("Gunner"),
+                source: entity,
+                status: None,
+            },
+            Collider,
+            CollisionLayers::new(LayerMask::ENEMY_PROJECTILE, LayerMask::PLAYER),
+            Velocity(*direction * ENEMY_PROJECTILE_SPEED),
+        ));
+        fired.send(EnemyFiredEvent { entity });
+    }
+}
+
 fn on_enemy_died(mut commands: Commands, mut ev_enemy_died: EventReader<EnemyDiedEvent>) {
     for event in ev_enemy_died.read() {
         commands.entity(event.entity).insert(Dead);
     }
 }
 
-fn spawn_enemies(mut commands: Commands, enemy_query: Query<Entity, With<Enemy>>) {
+fn spawn_enemies(
+    mut commands: Commands,
+    mut materials: EffectMaterials,
+    assets: Res<GameAssets>,
+    enemy_query: Query<Entity, With<Enemy>>,
+    player_query: Option<Single<Entity, With<Player>>>,
+) {
     let n_enemies = enemy_query.iter().count();
     if n_enemies > 0 {
         return;
@@ -136,21 +305,79 @@ fn spawn_enemies(mut commands: Commands, enemy_query: Query<Entity, With<Enemy>>
     let y = TOP - 100.0;
     let pos = Vec2::new(x, y);
 
-    //commands.spawn((
-    //    Sprite {
-    //        color: BRICK_COLOR,
-    //        ..default()
-    //    },
-    //    Transform {
-    //        translation: pos.extend(0.0),
-    //        scale: Vec3::new(PROJECTILE_SIZE, PROJECTILE_SIZE, 1.0),
-    //        ..default()
-    //    },
-    //    Enemy::new(EnemyType::Creep),
-    //    Health(2),
-    //    Collider,
-    //    Velocity(Vec2::new(0., -1.) * PROJECTILE_SPEED),
-    //));
+    if let Some(player) = player_query.as_deref()
+        && rand::random_range(0.0..1.0) > 0.97
+    {
+        // creeps wander towards the player, some circle it and a few are skittish
+        let target = SteeringTarget::Entity(*player);
+        let mut creep = commands.spawn((
+            Name::new("Enemy"),
+            Mesh2d(assets.enemy_mesh.clone()),
+            MeshMaterial2d(materials.add(Sheet::Creep)),
+            SpriteAnimation::new(Sheet::Creep, AnimationState::Idle),
+            MaterialEffects::default(),
+            Transform {
+                translation: pos.extend(0.0),
+                scale: Vec3::new(PROJECTILE_SIZE, PROJECTILE_SIZE, 1.0),
+                ..default()
+            },
+            Enemy::new(EnemyType::Creep),
+            Health::new(2.),
+            // creeps burn easily
+            Resistances {
+                fire: -0.5,
+                ..default()
+            },
+            Collider,
+            CollisionLayers::new(
+                LayerMask::ENEMY,
+                LayerMask::PLAYER | LayerMask::PLAYER_PROJECTILE,
+            ),
+            Wander::new(50., 100., 10.),
+        ));
+        match rand::random_range(0..4) {
+            0 => creep.insert(Orbit {
+                center: target,
+                radius: CREEP_ORBIT_RADIUS,
+            }),
+            1 => creep.insert(Flee(target)),
+            _ => creep.insert(Seek(target)),
+        };
+        return;
+    }
+
+    if player_query.is_some() && rand::random_range(0.0..1.0) > 0.98 {
+        // gunners fly in and hold a station near the top
+        let station = Vec2::new(
+            rand::random_range(LEFT / 2.0..RIGHT / 2.0),
+            GUNNER_STATION_Y,
+        );
+        commands.spawn((
+            Name::new("Enemy"),
+            Mesh2d(assets.enemy_mesh.clone()),
+            MeshMaterial2d(materials.add(Sheet::Standard)),
+            SpriteAnimation::new(Sheet::Standard, AnimationState::Idle),
+            MaterialEffects::default(),
+            Transform {
+                translation: pos.extend(0.0),
+                scale: Vec3::new(PROJECTILE_SIZE * 1.5, PROJECTILE_SIZE * 1.5, 1.0),
+                ..default()
+            },
+            Enemy::new(EnemyType::Gunner),
+            EnemyGun::default(),
+            Health::new(3.),
+            Collider,
+            CollisionLayers::new(
+                LayerMask::ENEMY,
+                LayerMask::PLAYER | LayerMask::PLAYER_PROJECTILE,
+            ),
+            Arrive {
+                target: SteeringTarget::Point(station),
+                slowing_radius: GUNNER_SLOWING_RADIUS,
+            },
+        ));
+        return;
+    }
 
     if rand::random_range(0.0..1.0) > 0.95 {
         // once in a while spawn a triangle path enemy
@@ -160,19 +387,29 @@ fn spawn_enemies(mut commands: Commands, enemy_query: Query<Entity, With<Enemy>>
 
         commands.spawn((
             Name::new("Enemy"),
-            Sprite {
-                color: BRICK_COLOR,
-                ..default()
-            },
+            Mesh2d(assets.enemy_mesh.clone()),
+            MeshMaterial2d(materials.add(Sheet::Standard)),
+            SpriteAnimation::new(Sheet::Standard, AnimationState::Idle),
+            MaterialEffects::default(),
             Transform {
                 translation: pos.extend(0.0),
                 scale: Vec3::new(PROJECTILE_SIZE * 2.1, PROJECTILE_SIZE * 2.1, 1.0),
                 ..default()
             },
             Enemy::new(EnemyType::Standard),
-            Health(4),
+            Health::new(4.),
+            Armor(0.25),
+            Resistances {
+                explosive: 0.3,
+                ..default()
+            },
             Collider,
+            CollisionLayers::new(
+                LayerMask::ENEMY,
+                LayerMask::PLAYER | LayerMask::PLAYER_PROJECTILE,
+            ),
             Velocity(Vec2::new(0., -1.) * 300.),
+            PathSpeed(300.),
             path,
         ));
     }
@@ -196,3 +433,87 @@ fn spawn_enemies(mut commands: Commands, enemy_query: Query<Entity, With<Enemy>>
     //    ));
     //}
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::schedule::SchedulePlugin;
+    use crate::world::combat::CombatPlugin;
+    use crate::world::status::{StatusEffect, StatusPlugin};
+
+    #[test]
+    fn stunned_enemies_drift_off_their_path() {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_millis(100));
+        world.insert_resource(time);
+
+        let drift = Vec2::new(50., 0.);
+        let mut spawn = |statuses| {
+            world
+                .spawn((
+                    Velocity(drift),
+                    Transform::default(),
+                    PolyPath::new(vec![Vec2::ZERO, Vec2::new(0., -100.)]),
+                    PathSpeed(300.),
+                    statuses,
+                ))
+                .id()
+        };
+        let mut stunned = StatusEffects::default();
+        stunned.apply(StatusEffect::Stun);
+        let stunned = spawn(stunned);
+        let normal = spawn(StatusEffects::default());
+
+        world.run_system_once(follow_path).unwrap();
+        assert_eq!(world.get::<Velocity>(stunned).unwrap().0, drift);
+        assert_eq!(world.get::<Velocity>(normal).unwrap().0, Vec2::NEG_Y * 300.);
+    }
+
+    #[test]
+    fn burn_kills_are_credited_to_the_player() {
+        let mut app = App::new();
+        app.add_plugins((SchedulePlugin, CombatPlugin, StatusPlugin))
+            .add_event::<EnemyDiedEvent>()
+            .add_systems(
+                FixedUpdate,
+                on_death.after(CombatSet).in_set(GameSet::Resolve),
+            );
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs(1));
+        app.insert_resource(time);
+
+        let player = app.world_mut().spawn(Player::new()).id();
+        let enemy = app
+            .world_mut()
+            .spawn((
+                Enemy::new(EnemyType::Standard),
+                Health::new(1.),
+                Transform::default(),
+            ))
+            .id();
+        // the laser sets the enemy on fire, the next tick burns it down
+        app.world_mut().send_event(DamageDealtEvent {
+            target: enemy,
+            source: Some(player),
+            position: Vec2::ZERO,
+            status: Some(StatusEffect::Burn),
+            critical: false,
+        });
+        app.world_mut().run_schedule(FixedUpdate);
+        app.world_mut().run_schedule(FixedUpdate);
+
+        let events = app.world().resource::<Events<EnemyDiedEvent>>();
+        let mut cursor = events.get_cursor();
+        let died: Vec<_> = cursor.read(events).collect();
+        assert_eq!(died.len(), 1);
+        assert_eq!(died[0].enemy_type, EnemyType::Standard);
+        assert_eq!(died[0].cause, DamageCause::Status(StatusEffect::Burn));
+        assert_eq!(died[0].player, Some(player));
+        assert_eq!(died[0].overkill, BURN_DAMAGE_PER_SEC - 1.);
+    }
+}
@@ -1,17 +1,191 @@
+use crate::prelude::constants::*;
 use crate::prelude::*;
 
+use super::combat::DamageKind;
+use super::status::StatusEffect;
+
+#[derive(Clone, Debug)]
 pub enum ProjectileType {
+    /// Projectiles side by side.
     Single,
+    /// Projectiles spread evenly over the given angle in radians.
     Fan(f32),
+    /// Projectiles in a row, one behind the other.
     Burst,
+    /// A continuous laser of the given width, `damage` is applied per second.
+    /// It burns through `pierce` enemies and stops at the next one.
+    Beam { width: f32, pierce: usize },
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Debug)]
 pub struct Weapon {
-    name: String,
-    fire_rate: f32,
-    damage: f32,
-    projectile_speed: f32,
-    projectile_type: ProjectileType,
-    projectiles_per_shot: usize,
+    pub name: &'static str,
+    pub fire_rate: f32,
+    pub damage: f32,
+    pub damage_kind: DamageKind,
+    pub projectile_speed: f32,
+    pub projectile_type: ProjectileType,
+    pub projectiles_per_shot: usize,
+    /// Inflicted on every enemy hit
+    pub status: Option<StatusEffect>,
+    /// Turn rate of homing projectiles in radians per second
+    pub homing: Option<f32>,
+}
+
+impl Weapon {
+    pub fn blaster() -> Self {
+        Weapon {
+            name: "Blaster",
+            fire_rate: 0.2,
+            damage: 1.,
+            damage_kind: DamageKind::Kinetic,
+            projectile_speed: PROJECTILE_SPEED,
+            projectile_type: ProjectileType::Single,
+            projectiles_per_shot: 1,
+            status: None,
+            homing: None,
+        }
+    }
+
+    pub fn spread() -> Self {
+        Weapon {
+            name: "Spread",
+            fire_rate: 0.35,
+            damage: 1.,
+            damage_kind: DamageKind::Kinetic,
+            projectile_speed: 400.,
+            projectile_type: ProjectileType::Fan(0.6),
+            projectiles_per_shot: 5,
+            status: Some(StatusEffect::Slow),
+            homing: None,
+        }
+    }
+
+    pub fn burst() -> Self {
+        Weapon {
+            name: "Burst",
+            fire_rate: 0.5,
+            damage: 1.,
+            damage_kind: DamageKind::Kinetic,
+            projectile_speed: 600.,
+            projectile_type: ProjectileType::Burst,
+            projectiles_per_shot: 3,
+            status: Some(StatusEffect::Freeze),
+            homing: None,
+        }
+    }
+
+    /// Missiles home in on the enemy closest to the ship when fired.
+    pub fn missiles() -> Self {
+        Weapon {
+            name: "Missiles",
+            fire_rate: 0.6,
+            damage: 2.,
+            damage_kind: DamageKind::Explosive,
+            projectile_speed: 350.,
+            projectile_type: ProjectileType::Single,
+            projectiles_per_shot: 2,
+            status: None,
+            homing: Some(MISSILE_TURN_RATE),
+        }
+    }
+
+    pub fn laser() -> Self {
+        Weapon {
+            name: "Laser",
+            fire_rate: 0.,
+            damage: 8.,
+            damage_kind: DamageKind::Energy,
+            projectile_speed: 0.,
+            projectile_type: ProjectileType::Beam {
+                width: 8.,
+                pierce: 1,
+            },
+            projectiles_per_shot: 0,
+            status: Some(StatusEffect::Burn),
+            homing: None,
+        }
+    }
+
+    pub fn is_beam(&self) -> bool {
+        matches!(self.projectile_type, ProjectileType::Beam { .. })
+    }
+
+    /// Offset and direction of every projectile of a single shot towards `direction`.
+    pub fn pattern(&self, direction: Vec2) -> Vec<(Vec2, Vec2)> {
+        let n = self.projectiles_per_shot;
+        let direction = direction.normalize();
+        let centered = |i: usize| i as f32 - (n as f32 - 1.) / 2.;
+
+        match self.projectile_type {
+            ProjectileType::Single => (0..n)
+                .map(|i| (direction.perp() * centered(i) * 15., direction))
+                .collect(),
+            ProjectileType::Fan(spread) => (0..n)
+                .map(|i| {
+                    let angle = if n > 1 {
+                        centered(i) * spread / (n as f32 - 1.)
+                    } else {
+                        0.
+                    };
+                    (Vec2::ZERO, Vec2::from_angle(angle).rotate(direction))
+                })
+                .collect(),
+            ProjectileType::Burst => (0..n)
+                .map(|i| (direction * i as f32 * 20., direction))
+                .collect(),
+            ProjectileType::Beam { .. } => vec![],
+        }
+    }
+}
+
+/// All weapons a ship carries, the active one is also inserted as `Weapon` component.
+#[derive(Component)]
+pub struct Loadout {
+    weapons: Vec<Weapon>,
+    current: usize,
+}
+
+impl Loadout {
+    pub fn new(weapons: Vec<Weapon>) -> Self {
+        Loadout {
+            weapons,
+            current: 0,
+        }
+    }
+
+    pub fn current(&self) -> &Weapon {
+        &self.weapons[self.current]
+    }
+
+    /// Select the next weapon and return it.
+    pub fn next(&mut self) -> &Weapon {
+        self.current = (self.current + 1) % self.weapons.len();
+        self.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fan_is_symmetric() {
+        let pattern = Weapon::spread().pattern(Vec2::Y);
+        assert_eq!(pattern.len(), 5);
+        assert_eq!(pattern[2].1, Vec2::Y, "middle projectile flies straight");
+        assert!((pattern[0].1.x + pattern[4].1.x).abs() < 1e-6);
+    }
+
+    #[test]
+    fn beam_has_no_projectiles() {
+        assert!(Weapon::laser().pattern(Vec2::Y).is_empty());
+    }
+
+    #[test]
+    fn loadout_cycles() {
+        let mut loadout = Loadout::new(vec![Weapon::blaster(), Weapon::laser()]);
+        assert_eq!(loadout.next().name, "Laser");
+        assert_eq!(loadout.next().name, "Blaster");
+    }
 }
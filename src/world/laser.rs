@@ -0,0 +1,141 @@
+use bevy::sprite::Anchor;
+
+use crate::game_assets::GameAssets;
+use crate::input::{Action, ActionState};
+use crate::physics::spatial_query::SpatialQuery;
+use crate::prelude::constants::*;
+use crate::prelude::physics::*;
+use crate::prelude::*;
+
+use super::combat::{Damage, DamageCause};
+use super::equipment::{ProjectileType, Weapon};
+use super::player::{Aim, Player};
+use super::power_up::PowerUps;
+
+/// The beam of a laser weapon, a child of the ship firing it.
+/// Damage per second is accumulated and sent as whole points via `Damage`.
+#[derive(Component, Default)]
+pub struct LaserBeam {
+    accumulated_damage: f32,
+}
+
+/// Flare at the point where the beam hits the last enemy.
+#[derive(Component)]
+pub struct LaserImpact;
+
+pub struct LaserPlugin;
+
+impl Plugin for LaserPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, fire_laser.in_set(GameSet::Collision));
+    }
+}
+
+/// Spawns the (hidden) beam and impact flare as children of a ship.
+pub fn spawn_beam(parent: &mut ChildBuilder, assets: &GameAssets) {
+    parent.spawn((
+        Name::new("LaserBeam"),
+        LaserBeam::default(),
+        Sprite {
+            color: LASER_COLOR,
+            anchor: Anchor::BottomCenter,
+            ..default()
+        },
+        Transform::from_translation(LASER_OFFSET.extend(-0.1)),
+        Visibility::Hidden,
+    ));
+    parent.spawn((
+        Name::new("LaserImpact"),
+        LaserImpact,
+        Mesh2d(assets.laser_impact_mesh.clone()),
+        MeshMaterial2d(assets.laser_impact_material.clone()),
+        Transform::from_translation(LASER_OFFSET.extend(0.1)),
+        Visibility::Hidden,
+    ));
+}
+
+/// The beam and its sprite, which is stretched to the length of the beam.
+type BeamQuery<'w> = Single<
+    'w,
+    (
+        &'static mut LaserBeam,
+        &'static mut Sprite,
+        &'static mut Transform,
+        &'static mut Visibility,
+    ),
+    (Without<LaserImpact>, Without<Player>),
+>;
+
+/// The impact flare, moved to the end of the beam.
+type ImpactQuery<'w> = Single<
+    'w,
+    (&'static mut Transform, &'static mut Visibility),
+    (With<LaserImpact>, Without<Collider>),
+>;
+
+fn fire_laser(
+    actions: Res<ActionState>,
+    time: Res<Time>,
+    player_query: Single<(Entity, &Transform, &Weapon, &Aim, &PowerUps), With<Player>>,
+    beam_query: BeamQuery,
+    impact_query: ImpactQuery,
+    spatial_query: SpatialQuery,
+    mut damage_events: EventWriter<Damage>,
+) {
+    let (player, player_transform, weapon, aim, power_ups) = player_query.into_inner();
+    let (mut beam, mut sprite, mut beam_transform, mut beam_visibility) = beam_query.into_inner();
+    let (mut impact_transform, mut impact_visibility) = impact_query.into_inner();
+
+    let ProjectileType::Beam { width, pierce } = weapon.projectile_type else {
+        *beam_visibility = Visibility::Hidden;
+        *impact_visibility = Visibility::Hidden;
+        return;
+    };
+
+    if !actions.pressed(Action::Fire) {
+        beam.accumulated_damage = 0.;
+        *beam_visibility = Visibility::Hidden;
+        *impact_visibility = Visibility::Hidden;
+        return;
+    }
+
+    // the beam points along the aim, starting at the tip of the ship
+    let direction = Dir2::new(**aim).unwrap_or(Dir2::Y);
+    let rotation = Quat::from_rotation_z(Vec2::Y.angle_to(*direction));
+    let offset = rotation.mul_vec3(LASER_OFFSET.extend(0.)).truncate();
+    let origin = player_transform.translation.truncate() + offset;
+    let mut hits = spatial_query.cast_ray_all(origin, direction, LASER_RANGE, LayerMask::ENEMY);
+    hits.truncate(pierce + 1);
+    let length = hits.get(pierce).map_or(LASER_RANGE, |hit| hit.distance);
+
+    sprite.custom_size = Some(Vec2::new(width, length));
+    beam_transform.translation = offset.extend(beam_transform.translation.z);
+    beam_transform.rotation = rotation;
+    *beam_visibility = Visibility::Inherited;
+
+    let Some(last) = hits.last() else {
+        *impact_visibility = Visibility::Hidden;
+        return;
+    };
+
+    impact_transform.translation =
+        (offset + *direction * last.distance).extend(impact_transform.translation.z);
+    *impact_visibility = Visibility::Inherited;
+
+    beam.accumulated_damage += weapon.damage * power_ups.damage_factor() * time.delta_secs();
+    let damage = beam.accumulated_damage.floor();
+    if damage >= 1. {
+        beam.accumulated_damage -= damage;
+        for hit in &hits {
+            damage_events.send(Damage {
+                target: hit.entity,
+                amount: damage,
+                kind: weapon.damage_kind,
+                cause: DamageCause::Weapon(weapon.name),
+                source: Some(player),
+                position: hit.point,
+                status: weapon.status,
+            });
+        }
+    }
+}
@@ -0,0 +1,125 @@
+/// Pickups dropped by dying enemies, they drift down and are collected by flying into them.
+use crate::game_assets::GameAssets;
+use crate::pool::{PoolCommands, PoolPlugin, Poolable};
+use crate::prelude::constants::*;
+use crate::prelude::physics::*;
+use crate::prelude::*;
+
+use super::player::Player;
+use super::power_up::PowerUp;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PickupKind {
+    /// One more bomb, see `bomb::Bombs`
+    Bomb,
+    PowerUp(PowerUp),
+}
+
+impl PickupKind {
+    pub const ALL: [PickupKind; 6] = [
+        PickupKind::Bomb,
+        PickupKind::PowerUp(PowerUp::Shield),
+        PickupKind::PowerUp(PowerUp::Speed),
+        PickupKind::PowerUp(PowerUp::RapidFire),
+        PickupKind::PowerUp(PowerUp::Damage),
+        PickupKind::PowerUp(PowerUp::Magnet),
+    ];
+
+    /// Chance of an enemy dropping the pickup when it dies.
+    fn drop_chance(&self) -> f32 {
+        match self {
+            PickupKind::Bomb => BOMB_PICKUP_DROP_CHANCE,
+            PickupKind::PowerUp(_) => POWER_UP_DROP_CHANCE,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            PickupKind::Bomb => BOMB_PICKUP_COLOR,
+            PickupKind::PowerUp(PowerUp::Shield) => SHIELD_COLOR,
+            PickupKind::PowerUp(PowerUp::Speed) => SPEED_PICKUP_COLOR,
+            PickupKind::PowerUp(PowerUp::RapidFire) => RAPID_FIRE_PICKUP_COLOR,
+            PickupKind::PowerUp(PowerUp::Damage) => DAMAGE_PICKUP_COLOR,
+            PickupKind::PowerUp(PowerUp::Magnet) => MAGNET_PICKUP_COLOR,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Pickup(pub PickupKind);
+
+impl Poolable for Pickup {}
+
+#[derive(Event)]
+pub struct PickupCollectedEvent {
+    pub kind: PickupKind,
+}
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(PoolPlugin::<Pickup>::default())
+            .add_event::<PickupCollectedEvent>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    on_collision.in_set(GameSet::Resolve),
+                    drop_pickups.in_set(GameSet::Cleanup),
+                ),
+            );
+    }
+}
+
+fn drop_pickups(
+    mut pickups: PoolCommands<Pickup>,
+    mut ev_enemy_died: EventReader<EnemyDiedEvent>,
+    assets: Res<GameAssets>,
+) {
+    for event in ev_enemy_died.read() {
+        // at most one pickup per enemy
+        let Some(kind) = PickupKind::ALL
+            .into_iter()
+            .find(|kind| rand::random_range(0.0..1.0) < kind.drop_chance())
+        else {
+            continue;
+        };
+
+        pickups.spawn((
+            Name::new("Pickup"),
+            Mesh2d(assets.pickup_mesh.clone()),
+            MeshMaterial2d(assets.pickup_material(kind)),
+            Transform {
+                translation: event.position.extend(0.0),
+                scale: Vec3::new(PICKUP_RADIUS, PICKUP_RADIUS, 1.0),
+                ..default()
+            },
+            Pickup(kind),
+            Collider,
+            CollisionLayers::new(LayerMask::PICKUP, LayerMask::PLAYER),
+            Velocity(Vec2::NEG_Y * PICKUP_SPEED),
+        ));
+    }
+}
+
+fn on_collision(
+    mut pickups: PoolCommands<Pickup>,
+    mut colission_events: EventReader<CollisionEvent>,
+    mut collected: EventWriter<PickupCollectedEvent>,
+    player_query: Single<Entity, With<Player>>,
+    pickup_query: Query<&Pickup>,
+) {
+    let player_entity = player_query.into_inner();
+    for event in colission_events.read() {
+        let pickup = match (event.entity1, event.entity2) {
+            (player, pickup) | (pickup, player) if player == player_entity => pickup,
+            _ => continue,
+        };
+        let Ok(Pickup(kind)) = pickup_query.get(pickup) else {
+            continue;
+        };
+
+        collected.send(PickupCollectedEvent { kind: *kind });
+        pickups.release(pickup);
+    }
+}
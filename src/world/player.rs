@@ -1,10 +1,30 @@
+use bevy::ecs::system::SystemParam;
+
 use crate::prelude::constants::*;
 use crate::prelude::physics::*;
 use crate::prelude::*;
 
+use crate::audio::{PlaySfx, Sfx};
+use crate::game_assets::GameAssets;
+use crate::graphics::animation::{AnimationState, Sheet, SpriteAnimation};
+use crate::graphics::effects::{EffectMaterials, MaterialEffects};
+use crate::graphics::particles::{ENGINE_TRAIL, ParticleEmitter};
+use crate::input::{Action, ActionState, ControlScheme};
+use crate::physics::spatial_query::SpatialQuery;
+use crate::physics::steering::{Homing, SteeringTarget};
+use crate::pool::{PoolCommands, PoolPlugin, Poolable};
+use crate::settings::Settings;
+
+use super::bomb::Bombs;
+use super::combat::{CombatSet, Critical, Damage, DamageCause, DamageKind, DeathEvent};
+use super::equipment::{Loadout, Weapon};
+use super::laser;
+use super::power_up::{self, PowerUps};
+use super::status::StatusEffect;
+
 #[derive(Component)]
+#[require(Velocity, Aim, Bombs, PowerUps)]
 pub struct Player {
-    fire_rate: f32,
     last_shot: f32,
 }
 
@@ -12,11 +32,8 @@ pub struct Player {
 pub struct PlayerHitByEnemyEvent;
 
 impl Player {
-    pub fn new(fire_rate: f32) -> Self {
-        Player {
-            fire_rate,
-            last_shot: 0.,
-        }
+    pub fn new() -> Self {
+        Player { last_shot: 0. }
     }
 
     pub fn set_last_shot(&mut self, last_shot: f32) {
@@ -24,174 +41,374 @@ impl Player {
     }
 }
 
+/// Direction the ship fires in, straight ahead unless it aims with the twin-stick scheme.
+#[derive(Component, Deref)]
+pub struct Aim(pub Vec2);
+
+impl Default for Aim {
+    fn default() -> Self {
+        Aim(INITITAL_PROJECTILE_DIRECTION)
+    }
+}
+
+/// Enemies pass through the player without hitting it, for the given seconds.
+#[derive(Component)]
+pub struct Invulnerable(pub f32);
+
 #[derive(Component)]
 pub struct PlayerProjectile;
 
+/// What a projectile does to the ship it hits.
+#[derive(Component)]
+pub struct ProjectileHit {
+    pub damage: f32,
+    pub kind: DamageKind,
+    pub cause: DamageCause,
+    /// The ship that fired the projectile
+    pub source: Entity,
+    pub status: Option<StatusEffect>,
+}
+
+impl Poolable for PlayerProjectile {
+    fn strip(entity: &mut EntityCommands) {
+        entity.remove::<(Self, Collider, Velocity, Interpolated, Homing)>();
+    }
+}
+
+/// Picks the enemy homing projectiles fly at: the one under the cursor, else the closest one.
+#[derive(SystemParam)]
+struct HomingTargets<'w, 's> {
+    spatial_query: SpatialQuery<'w, 's>,
+    enemy_query: Query<'w, 's, (Entity, &'static Transform), With<Enemy>>,
+}
+
+impl HomingTargets<'_, '_> {
+    fn pick(&self, from: Vec2, cursor: Option<Vec2>) -> Option<Entity> {
+        let under_cursor = cursor.and_then(|cursor| {
+            let enemies = self
+                .spatial_query
+                .point_intersections(cursor, LayerMask::ENEMY);
+            enemies.first().copied()
+        });
+        under_cursor.or_else(|| {
+            self.enemy_query
+                .iter()
+                .map(|(enemy, transform)| (enemy, transform.translation.truncate().distance(from)))
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(enemy, _)| enemy)
+        })
+    }
+}
+
+/// Acceleration of a ship while a direction is pressed.
+/// Together with `LinearDrag` and `MaxSpeed` this defines how a ship handles.
+#[derive(Component, Deref, DerefMut)]
+pub struct Thrust(pub f32);
+
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<PlayerHitByEnemyEvent>()
+        app.add_plugins(PoolPlugin::<PlayerProjectile>::default())
+            .add_event::<PlayerHitByEnemyEvent>()
             .add_systems(Startup, (spawn,))
-            .add_systems(Update, (control, shoot, on_collision));
+            .add_systems(
+                FixedUpdate,
+                (
+                    (
+                        control,
+                        switch_weapon,
+                        (aim, shoot).chain(),
+                        wear_off_invulnerability,
+                    )
+                        .in_set(GameSet::Input),
+                    confine.in_set(GameSet::Collision),
+                    on_collision.before(CombatSet).in_set(GameSet::Resolve),
+                    rebuild.after(CombatSet).in_set(GameSet::Resolve),
+                ),
+            );
     }
 }
 
-fn spawn(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-) {
+fn spawn(mut commands: Commands, mut materials: EffectMaterials, assets: Res<GameAssets>) {
     // Player
     let player_y = BOTTOM + GAP_BETWEEN_PADDLE_AND_FLOOR;
 
-    let player_mesh = meshes.add(Triangle2d::new(
-        Vec2::Y * 30.0,
-        Vec2::new(-30.0, -30.0),
-        Vec2::new(30.0, -30.0),
-    ));
-    let color = Color::hsl(0.8, 0.95, 0.7);
-
-    commands.spawn((
-        Name::new("Player"),
-        Mesh2d(player_mesh),
-        MeshMaterial2d(materials.add(color)),
-        Transform {
-            translation: Vec3::new(0.0, player_y, 0.0),
-            ..default()
-        },
-        Player::new(0.2),
-        Collider,
-        Momentum(Vec2::new(0., 0.)),
-    ));
+    let loadout = Loadout::new(vec![
+        Weapon::blaster(),
+        Weapon::spread(),
+        Weapon::burst(),
+        Weapon::missiles(),
+        Weapon::laser(),
+    ]);
+
+    commands
+        .spawn((
+            Name::new("Player"),
+            (
+                Mesh2d(assets.player_mesh.clone()),
+                MeshMaterial2d(materials.add(Sheet::Player)),
+                SpriteAnimation::new(Sheet::Player, AnimationState::Idle),
+                MaterialEffects::default(),
+            ),
+            Transform {
+                translation: Vec3::new(0.0, player_y, 0.0),
+                ..default()
+            },
+            Player::new(),
+            (
+                Health::new(PLAYER_HEALTH),
+                Critical {
+                    chance: PLAYER_CRIT_CHANCE,
+                    multiplier: PLAYER_CRIT_MULTIPLIER,
+                },
+            ),
+            loadout.current().clone(),
+            loadout,
+            Collider,
+            CollisionLayers::new(
+                LayerMask::PLAYER,
+                LayerMask::ENEMY | LayerMask::ENEMY_PROJECTILE | LayerMask::PICKUP,
+            ),
+            Thrust(PLAYER_THRUST),
+            Acceleration(Vec2::ZERO),
+            LinearDrag(PLAYER_DRAG),
+            MaxSpeed(PLAYER_MAX_SPEED),
+            ParticleEmitter::new(ENGINE_TRAIL, 60., Vec2::new(0., -30.)),
+        ))
+        .with_children(|parent| {
+            laser::spawn_beam(parent, &assets);
+            power_up::spawn_shield(parent, &assets);
+        });
 }
 
+/// Enemies and enemy projectiles, only the projectiles carry a `ProjectileHit`.
+type AttackerQuery<'w, 's> =
+    Query<'w, 's, Option<&'static ProjectileHit>, Or<(With<Enemy>, With<EnemyProjectile>)>>;
+
+/// Enemies ramming the ship and enemy projectiles damage it, a shield absorbs the hit instead.
+/// The player is briefly invulnerable afterwards, as a collision lasts for several ticks.
 fn on_collision(
+    mut commands: Commands,
+    mut enemy_projectiles: PoolCommands<EnemyProjectile>,
     mut colission_events: EventReader<CollisionEvent>,
     mut player_enemy_colission_events: EventWriter<PlayerHitByEnemyEvent>,
-    player_query: Single<Entity, With<Player>>,
-    enemy_query: Query<Entity, With<Enemy>>,
+    mut damage_events: EventWriter<Damage>,
+    player_query: Single<(Entity, &Transform, &mut PowerUps, Has<Invulnerable>), With<Player>>,
+    attacker_query: AttackerQuery,
 ) {
-    let player_entity = player_query.into_inner();
+    let (player_entity, transform, mut power_ups, mut invulnerable) = player_query.into_inner();
     for event in colission_events.read() {
-        let (e1, e2) = (event.entity1, event.entity2);
-        let is_player = e1 == player_entity || e2 == player_entity;
+        let other = match (event.entity1, event.entity2) {
+            (player, other) | (other, player) if player == player_entity => other,
+            _ => continue,
+        };
+        let Ok(projectile) = attacker_query.get(other) else {
+            continue;
+        };
+        if projectile.is_some() {
+            enemy_projectiles.release(other);
+        }
+        if invulnerable {
+            continue;
+        }
 
-        if is_player && (enemy_query.contains(e1) || enemy_query.contains(e2)) {
-            let player_hit_by_enemy_event = PlayerHitByEnemyEvent::default();
-            player_enemy_colission_events.send(player_hit_by_enemy_event);
+        invulnerable = true;
+        if power_ups.absorb_hit() {
+            commands
+                .entity(player_entity)
+                .insert(Invulnerable(SHIELD_INVULNERABILITY_SECS));
+            continue;
         }
+        commands
+            .entity(player_entity)
+            .insert(Invulnerable(PLAYER_INVULNERABILITY_SECS));
+        let position = transform.translation.truncate();
+        damage_events.send(match projectile {
+            Some(hit) => Damage {
+                target: player_entity,
+                amount: hit.damage,
+                kind: hit.kind,
+                cause: hit.cause,
+                source: Some(hit.source),
+                position,
+                status: hit.status,
+            },
+            None => Damage {
+                target: player_entity,
+                amount: ENEMY_CONTACT_DAMAGE,
+                kind: DamageKind::Kinetic,
+                cause: DamageCause::Contact,
+                source: Some(other),
+                position,
+                status: None,
+            },
+        });
+        let player_hit_by_enemy_event = PlayerHitByEnemyEvent::default();
+        player_enemy_colission_events.send(player_hit_by_enemy_event);
     }
 }
 
-fn control(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    query: Single<(&mut Transform, &mut Momentum), With<Player>>,
-    time: Res<Time>,
+/// There is no game over yet, a destroyed ship is repaired and briefly invulnerable.
+fn rebuild(
+    mut commands: Commands,
+    mut events: EventReader<DeathEvent>,
+    query: Single<(Entity, &mut Health), With<Player>>,
 ) {
-    let (mut paddle_transform, mut momentum) = query.into_inner();
-
-    let mut dx = 0.0;
-    let mut dy = 0.0;
-    let mut mx = momentum.0.x;
-    let mut my = momentum.0.y;
-    let mut pressed = false;
-
-    if keyboard_input.pressed(KeyCode::ArrowLeft) {
-        dx -= 1.0;
-        mx -= 0.1;
-        pressed = true;
-    }
-
-    if keyboard_input.pressed(KeyCode::ArrowRight) {
-        dx += 1.0;
-        mx += 0.1;
-        pressed = true;
-    }
-
-    if keyboard_input.pressed(KeyCode::ArrowDown) {
-        dy -= 1.0;
-        my -= 0.1;
-        pressed = true;
-    }
-
-    if keyboard_input.pressed(KeyCode::ArrowUp) {
-        dy += 1.0;
-        my += 0.1;
-        pressed = true;
+    let (player, mut health) = query.into_inner();
+    for event in events.read() {
+        if event.entity == player {
+            health.current = health.max;
+            commands
+                .entity(player)
+                .insert(Invulnerable(PLAYER_INVULNERABILITY_SECS));
+        }
     }
+}
 
-    if !pressed {
-        mx *= 0.91;
-        my *= 0.91;
+fn wear_off_invulnerability(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Invulnerable)>,
+    time: Res<Time>,
+) {
+    for (entity, mut invulnerable) in &mut query {
+        invulnerable.0 -= time.delta_secs();
+        if invulnerable.0 <= 0. {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
     }
+}
 
-    mx = mx.clamp(-1., 1.);
-    my = my.clamp(-1., 1.);
+fn control(
+    actions: Res<ActionState>,
+    query: Single<(&mut Acceleration, &mut MaxSpeed, &Thrust, &PowerUps), With<Player>>,
+) {
+    let (mut acceleration, mut max_speed, thrust, power_ups) = query.into_inner();
 
-    momentum.0.x = mx;
-    momentum.0.y = my;
+    // analog sticks give proportional thrust, diagonals are as fast as straight movement
+    acceleration.0 = actions.movement() * thrust.0 * power_ups.speed_factor();
+    max_speed.0 = PLAYER_MAX_SPEED * power_ups.speed_factor();
+}
 
-    // Calculate the new horizontal paddle position based on player input
-    let new_paddle_xposition =
-        paddle_transform.translation.x + ((dx + mx) * PADDLE_SPEED) * time.delta_secs();
-    let new_paddle_yposition =
-        paddle_transform.translation.y + ((dy + my) * PADDLE_SPEED) * time.delta_secs();
+/// Keeps the player inside the arena, stopping the ship when it hits a border.
+fn confine(query: Single<(&mut Transform, &mut Velocity), With<Player>>) {
+    let (mut transform, mut velocity) = query.into_inner();
 
-    // Update the paddle position,
-    // making sure it doesn't cause the paddle to leave the arena
     let left_bound = LEFT + PADDLE_SIZE.x / 2.0 + PADDLE_PADDING;
     let right_bound = RIGHT - PADDLE_SIZE.x / 2.0 - PADDLE_PADDING;
 
     let upper_bound = TOP - PADDLE_SIZE.y / 2.0;
     let lower_bound = BOTTOM + PADDLE_SIZE.y / 2.0;
 
-    paddle_transform.translation.x = new_paddle_xposition.clamp(left_bound, right_bound);
-    paddle_transform.translation.y = new_paddle_yposition.clamp(lower_bound, upper_bound);
+    let x = transform.translation.x.clamp(left_bound, right_bound);
+    let y = transform.translation.y.clamp(lower_bound, upper_bound);
+
+    if x != transform.translation.x {
+        velocity.x = 0.0;
+    }
+    if y != transform.translation.y {
+        velocity.y = 0.0;
+    }
+
+    transform.translation.x = x;
+    transform.translation.y = y;
+}
+
+/// Without any aim, e.g. with the cursor on the ship, the ship keeps its last aim.
+fn aim(
+    actions: Res<ActionState>,
+    settings: Res<Settings>,
+    query: Single<(&mut Aim, &Transform), With<Player>>,
+) {
+    let (mut aim, transform) = query.into_inner();
+    match settings.input.scheme {
+        ControlScheme::Classic => aim.0 = INITITAL_PROJECTILE_DIRECTION,
+        ControlScheme::TwinStick => {
+            if let Some(direction) = actions.aim(transform.translation.truncate()) {
+                aim.0 = direction;
+            }
+        }
+    }
+}
+
+fn switch_weapon(
+    actions: Res<ActionState>,
+    query: Single<(&mut Loadout, &mut Weapon), With<Player>>,
+) {
+    if !actions.fixed_just_pressed(Action::SwitchWeapon) {
+        return;
+    }
+
+    let (mut loadout, mut weapon) = query.into_inner();
+    *weapon = loadout.next().clone();
 }
 
-// TODO: maybe we put all keyboard interactions into one and then send events
 fn shoot(
-    mut commands: Commands,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut projectiles: PoolCommands<PlayerProjectile>,
+    actions: Res<ActionState>,
     time: Res<Time>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    player_query: Single<(&mut Player, &Transform), With<Player>>,
+    assets: Res<GameAssets>,
+    player_query: Single<(Entity, &mut Player, &Transform, &Weapon, &Aim, &PowerUps)>,
+    targets: HomingTargets,
+    mut sfx: EventWriter<PlaySfx>,
 ) {
-    if !keyboard_input.pressed(KeyCode::Space) {
+    if !actions.pressed(Action::Fire) {
         return;
     }
 
-    let (mut player, player_transform) = player_query.into_inner();
+    let (entity, mut player, player_transform, weapon, aim, power_ups) = player_query.into_inner();
+
+    // beams are fired continuously, see `laser::fire_laser`
+    if weapon.is_beam() {
+        return;
+    }
 
     let t = time.elapsed_secs();
 
-    if player.last_shot > 0. {
-        if player.last_shot + player.fire_rate > t {
-            return;
-        }
+    let fire_rate = weapon.fire_rate * power_ups.fire_rate_factor();
+    if player.last_shot > 0. && player.last_shot + fire_rate > t {
+        return;
     }
 
     player.set_last_shot(t);
+    sfx.send(PlaySfx(Sfx::Shoot));
 
     let pos = Vec2::new(
         player_transform.translation.x,
         player_transform.translation.y,
     );
 
-    let projectile_mesh = meshes.add(Ellipse::new(5.0, 10.0));
-    commands.spawn((
-        Name::new("PlayerProjectile"),
-        Mesh2d(projectile_mesh),
-        MeshMaterial2d(materials.add(PROJECTILE_COLOR)),
-        Transform {
-            translation: pos.extend(0.0),
-            ..default()
-        },
-        PlayerProjectile,
-        Collider,
-        Velocity(INITITAL_PROJECTILE_DIRECTION.normalize() * PROJECTILE_SPEED),
-        PointLight::default(),
-    ));
+    let damage = weapon.damage * power_ups.damage_factor();
+    let target = weapon
+        .homing
+        .and_then(|_| targets.pick(pos, actions.cursor()));
+    for (offset, direction) in weapon.pattern(**aim) {
+        let projectile = projectiles.spawn((
+            Name::new("PlayerProjectile"),
+            Mesh2d(assets.projectile_mesh.clone()),
+            MeshMaterial2d(assets.projectile_material.clone()),
+            Transform {
+                translation: (pos + offset).extend(0.0),
+                rotation: Quat::from_rotation_z(Vec2::Y.angle_to(direction)),
+                ..default()
+            },
+            PlayerProjectile,
+            ProjectileHit {
+                damage,
+                kind: weapon.damage_kind,
+                cause: DamageCause::Weapon(weapon.name),
+                source: entity,
+                status: weapon.status,
+            },
+            Collider,
+            CollisionLayers::new(LayerMask::PLAYER_PROJECTILE, LayerMask::ENEMY),
+            Velocity(direction * weapon.projectile_speed),
+        ));
+        if let (Some(turn_rate), Some(target)) = (weapon.homing, target) {
+            projectiles.entity(projectile).insert(Homing {
+                target: SteeringTarget::Entity(target),
+                turn_rate,
+            });
+        }
+    }
 }
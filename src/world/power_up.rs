@@ -0,0 +1,281 @@
+/// Timed status effects of the player, granted by pickups and removed once they expire.
+use crate::game_assets::GameAssets;
+use crate::prelude::constants::*;
+use crate::prelude::physics::*;
+use crate::prelude::*;
+
+use super::pickup::{Pickup, PickupCollectedEvent, PickupKind};
+use super::player::Player;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PowerUp {
+    /// Absorbs enemy hits, one stack per hit
+    Shield,
+    Speed,
+    RapidFire,
+    /// Multiplies the damage of every weapon, stronger with each stack
+    Damage,
+    /// Pulls nearby pickups towards the ship
+    Magnet,
+}
+
+/// What happens when a power-up is collected while it is still active.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Stacking {
+    /// The duration starts over
+    Refresh,
+    /// The durations add up to at most the given seconds
+    Extend(f32),
+    /// Adds `add` stacks up to `max` and the duration starts over
+    Stack { add: u32, max: u32 },
+}
+
+impl PowerUp {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PowerUp::Shield => "Shield",
+            PowerUp::Speed => "Speed",
+            PowerUp::RapidFire => "Rapid fire",
+            PowerUp::Damage => "Damage",
+            PowerUp::Magnet => "Magnet",
+        }
+    }
+
+    fn duration(&self) -> f32 {
+        match self {
+            PowerUp::Shield => SHIELD_SECS,
+            PowerUp::Speed => SPEED_BOOST_SECS,
+            PowerUp::RapidFire => RAPID_FIRE_SECS,
+            PowerUp::Damage => DAMAGE_BOOST_SECS,
+            PowerUp::Magnet => MAGNET_SECS,
+        }
+    }
+
+    fn stacking(&self) -> Stacking {
+        match self {
+            PowerUp::Shield => Stacking::Stack {
+                add: SHIELD_HITS,
+                max: SHIELD_MAX_HITS,
+            },
+            PowerUp::Speed | PowerUp::Magnet => Stacking::Refresh,
+            PowerUp::RapidFire => Stacking::Extend(RAPID_FIRE_MAX_SECS),
+            PowerUp::Damage => Stacking::Stack {
+                add: 1,
+                max: DAMAGE_BOOST_MAX_STACKS,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActivePowerUp {
+    pub power_up: PowerUp,
+    pub secs_left: f32,
+    pub stacks: u32,
+}
+
+/// Power-ups the player currently has, at most one entry per `PowerUp`.
+#[derive(Component, Default, Debug)]
+pub struct PowerUps {
+    active: Vec<ActivePowerUp>,
+}
+
+impl PowerUps {
+    pub fn active(&self) -> &[ActivePowerUp] {
+        &self.active
+    }
+
+    pub fn stacks(&self, power_up: PowerUp) -> u32 {
+        self.active
+            .iter()
+            .find(|active| active.power_up == power_up)
+            .map_or(0, |active| active.stacks)
+    }
+
+    pub fn has(&self, power_up: PowerUp) -> bool {
+        self.stacks(power_up) > 0
+    }
+
+    pub fn apply(&mut self, power_up: PowerUp) {
+        let duration = power_up.duration();
+        let Some(active) = self.active.iter_mut().find(|a| a.power_up == power_up) else {
+            let stacks = match power_up.stacking() {
+                Stacking::Stack { add, max } => add.min(max),
+                _ => 1,
+            };
+            self.active.push(ActivePowerUp {
+                power_up,
+                secs_left: duration,
+                stacks,
+            });
+            return;
+        };
+
+        match power_up.stacking() {
+            Stacking::Refresh => active.secs_left = duration,
+            Stacking::Extend(max) => active.secs_left = (active.secs_left + duration).min(max),
+            Stacking::Stack { add, max } => {
+                active.stacks = (active.stacks + add).min(max);
+                active.secs_left = duration;
+            }
+        }
+    }
+
+    /// Uses up a shield stack, returns whether there was one to absorb the hit.
+    pub fn absorb_hit(&mut self) -> bool {
+        let Some(shield) = self
+            .active
+            .iter_mut()
+            .find(|active| active.power_up == PowerUp::Shield)
+        else {
+            return false;
+        };
+        shield.stacks -= 1;
+        self.active.retain(|active| active.stacks > 0);
+        true
+    }
+
+    /// Factor for thrust and top speed of the ship.
+    pub fn speed_factor(&self) -> f32 {
+        if self.has(PowerUp::Speed) {
+            SPEED_BOOST_FACTOR
+        } else {
+            1.
+        }
+    }
+
+    /// Factor for the time between two shots.
+    pub fn fire_rate_factor(&self) -> f32 {
+        if self.has(PowerUp::RapidFire) {
+            1. / RAPID_FIRE_FACTOR
+        } else {
+            1.
+        }
+    }
+
+    pub fn damage_factor(&self) -> f32 {
+        1. + DAMAGE_BOOST_PER_STACK * self.stacks(PowerUp::Damage) as f32
+    }
+
+    fn tick(&mut self, dt: f32) {
+        for active in &mut self.active {
+            active.secs_left -= dt;
+        }
+        self.active.retain(|active| active.secs_left > 0.);
+    }
+}
+
+/// Bubble around the ship while the shield is up.
+#[derive(Component)]
+pub struct ShieldBubble;
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                (tick, attract_pickups).in_set(GameSet::Input),
+                on_pickup_collected.in_set(GameSet::Cleanup),
+            ),
+        )
+        .add_systems(Update, show_shield);
+    }
+}
+
+/// Spawns the (hidden) shield bubble as a child of a ship.
+pub fn spawn_shield(parent: &mut ChildBuilder, assets: &GameAssets) {
+    parent.spawn((
+        Name::new("ShieldBubble"),
+        ShieldBubble,
+        Mesh2d(assets.ring_mesh.clone()),
+        MeshMaterial2d(assets.shield_material.clone()),
+        Transform::from_xyz(0., 0., 0.2).with_scale(Vec3::new(SHIELD_RADIUS, SHIELD_RADIUS, 1.)),
+        Visibility::Hidden,
+    ));
+}
+
+fn tick(mut query: Query<&mut PowerUps>, time: Res<Time>) {
+    for mut power_ups in &mut query {
+        power_ups.tick(time.delta_secs());
+    }
+}
+
+fn on_pickup_collected(
+    mut events: EventReader<PickupCollectedEvent>,
+    query: Single<&mut PowerUps, With<Player>>,
+) {
+    let mut power_ups = query.into_inner();
+    for event in events.read() {
+        if let PickupKind::PowerUp(power_up) = event.kind {
+            power_ups.apply(power_up);
+        }
+    }
+}
+
+/// With the magnet, pickups in range fly towards the ship instead of drifting down.
+fn attract_pickups(
+    player_query: Single<(&Transform, &PowerUps), With<Player>>,
+    mut pickup_query: Query<(&Transform, &mut Velocity), With<Pickup>>,
+) {
+    let (player_transform, power_ups) = player_query.into_inner();
+    if !power_ups.has(PowerUp::Magnet) {
+        return;
+    }
+
+    let player = player_transform.translation.truncate();
+    for (transform, mut velocity) in &mut pickup_query {
+        let offset = player - transform.translation.truncate();
+        if offset.length() < MAGNET_RADIUS {
+            velocity.0 = offset.normalize_or_zero() * MAGNET_SPEED;
+        }
+    }
+}
+
+fn show_shield(
+    player_query: Single<&PowerUps, (With<Player>, Changed<PowerUps>)>,
+    mut bubble_query: Query<&mut Visibility, With<ShieldBubble>>,
+) {
+    let shielded = player_query.has(PowerUp::Shield);
+    for mut visibility in &mut bubble_query {
+        *visibility = if shielded {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_ups_stack_and_expire() {
+        let mut power_ups = PowerUps::default();
+        power_ups.apply(PowerUp::Shield);
+        power_ups.apply(PowerUp::Shield);
+        assert_eq!(power_ups.stacks(PowerUp::Shield), SHIELD_HITS * 2);
+
+        // rapid fire adds up to its maximum
+        power_ups.apply(PowerUp::RapidFire);
+        power_ups.tick(1.);
+        power_ups.apply(PowerUp::RapidFire);
+        let rapid_fire = &power_ups.active()[1];
+        assert_eq!(
+            rapid_fire.secs_left,
+            RAPID_FIRE_MAX_SECS.min(2. * RAPID_FIRE_SECS - 1.)
+        );
+
+        // the shield breaks after absorbing all of its hits
+        for _ in 0..SHIELD_HITS * 2 {
+            assert!(power_ups.absorb_hit());
+        }
+        assert!(!power_ups.absorb_hit());
+
+        power_ups.tick(RAPID_FIRE_MAX_SECS);
+        assert!(power_ups.active().is_empty());
+        assert_eq!(power_ups.fire_rate_factor(), 1.);
+    }
+}
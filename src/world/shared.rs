@@ -1,8 +1,18 @@
 /// Shared components for the game
 use crate::prelude::*;
 
-#[derive(Component)]
-pub struct Health(pub i32);
+/// Hit points of anything that can be destroyed, see `combat` for how damage is applied.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Health { current: max, max }
+    }
+}
 
 #[derive(Component, Default)]
 pub struct Dead;
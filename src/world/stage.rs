@@ -0,0 +1,42 @@
+use crate::prelude::constants::*;
+use crate::prelude::*;
+
+/// Progress through the game, a new stage begins every `STAGE_KILLS` kills.
+#[derive(Resource, Default)]
+pub struct Stage {
+    pub number: usize,
+    kills: usize,
+}
+
+/// Sent whenever a new stage begins, e.g. to change the background theme.
+#[derive(Event)]
+pub struct StageChangedEvent {
+    pub stage: usize,
+}
+
+pub struct StagePlugin;
+
+impl Plugin for StagePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Stage>()
+            .add_event::<StageChangedEvent>()
+            .add_systems(FixedUpdate, advance_stage.in_set(GameSet::Cleanup));
+    }
+}
+
+fn advance_stage(
+    mut stage: ResMut<Stage>,
+    mut ev_enemy_died: EventReader<EnemyDiedEvent>,
+    mut ev_stage_changed: EventWriter<StageChangedEvent>,
+) {
+    for _ in ev_enemy_died.read() {
+        stage.kills += 1;
+        if stage.kills >= STAGE_KILLS {
+            stage.kills = 0;
+            stage.number += 1;
+            ev_stage_changed.send(StageChangedEvent {
+                stage: stage.number,
+            });
+        }
+    }
+}
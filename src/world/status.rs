@@ -0,0 +1,260 @@
+/// Status effects weapons inflict on enemies: burn, slow, freeze and stun.
+use crate::physics::steering::{Steering, SteeringSet};
+use crate::prelude::constants::*;
+use crate::prelude::physics::*;
+use crate::prelude::*;
+
+use super::combat::{CombatSet, Damage, DamageCause, DamageDealtEvent, DamageKind};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusEffect {
+    /// Damage over time, stronger with each stack
+    Burn,
+    /// Moves slower
+    Slow,
+    /// Stops moving entirely
+    Freeze,
+    /// Stops acting, i.e. no steering or firing, but keeps drifting
+    Stun,
+}
+
+impl StatusEffect {
+    fn duration(&self) -> f32 {
+        match self {
+            StatusEffect::Burn => BURN_SECS,
+            StatusEffect::Slow => SLOW_SECS,
+            StatusEffect::Freeze => FREEZE_SECS,
+            StatusEffect::Stun => STUN_SECS,
+        }
+    }
+
+    /// Tint of an affected enemy.
+    pub fn color(&self) -> Color {
+        match self {
+            StatusEffect::Burn => BURN_TINT_COLOR,
+            StatusEffect::Slow => SLOW_TINT_COLOR,
+            StatusEffect::Freeze => FREEZE_TINT_COLOR,
+            StatusEffect::Stun => STUN_TINT_COLOR,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ActiveStatus {
+    effect: StatusEffect,
+    secs_left: f32,
+    stacks: u32,
+}
+
+/// Status effects an enemy currently suffers from, at most one entry per `StatusEffect`.
+#[derive(Component, Default, Debug)]
+pub struct StatusEffects {
+    active: Vec<ActiveStatus>,
+    /// Seconds after a freeze in which the enemy can't be frozen again
+    freeze_immunity: f32,
+    /// Burn damage not yet dealt as whole points
+    burn_damage: f32,
+    /// Whoever set the enemy on fire last, credited with the burn damage
+    burn_source: Option<Entity>,
+}
+
+impl StatusEffects {
+    pub fn has(&self, effect: StatusEffect) -> bool {
+        self.active.iter().any(|active| active.effect == effect)
+    }
+
+    fn stacks(&self, effect: StatusEffect) -> u32 {
+        self.active
+            .iter()
+            .find(|active| active.effect == effect)
+            .map_or(0, |active| active.stacks)
+    }
+
+    /// Burn stacks up and restarts, slow restarts, stun lasts for the longer of both durations.
+    /// Freezing is ignored while frozen and shortly afterwards, fire and ice cancel each other.
+    pub fn apply(&mut self, effect: StatusEffect) {
+        let duration = effect.duration();
+        match effect {
+            StatusEffect::Freeze if self.has(effect) || self.freeze_immunity > 0. => return,
+            StatusEffect::Freeze => self.remove(StatusEffect::Burn),
+            StatusEffect::Burn => self.remove(StatusEffect::Freeze),
+            _ => {}
+        }
+
+        let Some(active) = self.active.iter_mut().find(|a| a.effect == effect) else {
+            self.active.push(ActiveStatus {
+                effect,
+                secs_left: duration,
+                stacks: 1,
+            });
+            return;
+        };
+        match effect {
+            StatusEffect::Burn => {
+                active.stacks = (active.stacks + 1).min(BURN_MAX_STACKS);
+                active.secs_left = duration;
+            }
+            StatusEffect::Slow => active.secs_left = duration,
+            StatusEffect::Stun => active.secs_left = active.secs_left.max(duration),
+            StatusEffect::Freeze => {}
+        }
+    }
+
+    fn remove(&mut self, effect: StatusEffect) {
+        if effect == StatusEffect::Freeze && self.has(effect) {
+            self.freeze_immunity = FREEZE_IMMUNITY_SECS;
+        }
+        self.active.retain(|active| active.effect != effect);
+    }
+
+    /// Factor for the speed of the enemy, zero while frozen.
+    pub fn speed_factor(&self) -> f32 {
+        if self.has(StatusEffect::Freeze) {
+            0.
+        } else if self.has(StatusEffect::Slow) {
+            SLOW_FACTOR
+        } else {
+            1.
+        }
+    }
+
+    /// Whether the enemy may steer and fire.
+    pub fn can_act(&self) -> bool {
+        !self.has(StatusEffect::Freeze) && !self.has(StatusEffect::Stun)
+    }
+
+    /// The most noticeable effect decides the tint.
+    pub fn tint(&self) -> Option<Color> {
+        [
+            StatusEffect::Freeze,
+            StatusEffect::Stun,
+            StatusEffect::Burn,
+            StatusEffect::Slow,
+        ]
+        .into_iter()
+        .find(|effect| self.has(*effect))
+        .map(|effect| effect.color())
+    }
+
+    /// Advances all durations, returns the whole points of burn damage dealt meanwhile.
+    fn tick(&mut self, dt: f32) -> f32 {
+        self.burn_damage += BURN_DAMAGE_PER_SEC * self.stacks(StatusEffect::Burn) as f32 * dt;
+        let damage = self.burn_damage.floor();
+        self.burn_damage -= damage;
+
+        self.freeze_immunity = (self.freeze_immunity - dt).max(0.);
+        for active in &mut self.active {
+            active.secs_left -= dt;
+        }
+        let thawed = self
+            .active
+            .iter()
+            .any(|active| active.effect == StatusEffect::Freeze && active.secs_left <= 0.);
+        if thawed {
+            self.remove(StatusEffect::Freeze);
+        }
+        self.active.retain(|active| active.secs_left > 0.);
+        if !self.has(StatusEffect::Burn) {
+            self.burn_damage = 0.;
+        }
+        damage
+    }
+}
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                (burn, hinder_steering.after(SteeringSet)).in_set(GameSet::Input),
+                on_damage_dealt.after(CombatSet).in_set(GameSet::Resolve),
+            ),
+        );
+    }
+}
+
+fn on_damage_dealt(
+    mut events: EventReader<DamageDealtEvent>,
+    mut query: Query<&mut StatusEffects>,
+) {
+    for event in events.read() {
+        if let Some(effect) = event.status
+            && let Ok(mut statuses) = query.get_mut(event.target)
+        {
+            statuses.apply(effect);
+            if effect == StatusEffect::Burn {
+                statuses.burn_source = event.source;
+            }
+        }
+    }
+}
+
+/// Ticks all effects and deals the burn damage as regular hits.
+fn burn(
+    mut query: Query<(Entity, &mut StatusEffects, &Transform)>,
+    mut damage_events: EventWriter<Damage>,
+    time: Res<Time>,
+) {
+    for (entity, mut statuses, transform) in &mut query {
+        let damage = statuses.tick(time.delta_secs());
+        if damage > 0. {
+            damage_events.send(Damage {
+                target: entity,
+                amount: damage,
+                kind: DamageKind::Fire,
+                cause: DamageCause::Status(StatusEffect::Burn),
+                source: statuses.burn_source,
+                position: transform.translation.truncate(),
+                status: None,
+            });
+        }
+    }
+}
+
+/// Enemies following a path are handled by `enemy::follow_path`.
+fn hinder_steering(
+    mut query: Query<(&StatusEffects, &Steering, &mut Acceleration, &mut Velocity)>,
+) {
+    for (statuses, steering, mut acceleration, mut velocity) in &mut query {
+        if !statuses.can_act() {
+            acceleration.0 = Vec2::ZERO;
+        }
+        let factor = statuses.speed_factor();
+        if factor < 1. {
+            velocity.0 = velocity.0.clamp_length_max(steering.max_speed * factor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_stack_and_expire() {
+        let mut statuses = StatusEffects::default();
+        statuses.apply(StatusEffect::Burn);
+        statuses.apply(StatusEffect::Burn);
+        assert_eq!(statuses.stacks(StatusEffect::Burn), 2);
+        let damage = statuses.tick(1.);
+        assert_eq!(damage, (2. * BURN_DAMAGE_PER_SEC).floor());
+
+        // ice puts out the fire and can't be renewed while frozen
+        statuses.apply(StatusEffect::Freeze);
+        assert!(!statuses.has(StatusEffect::Burn));
+        assert_eq!(statuses.speed_factor(), 0.);
+        statuses.tick(FREEZE_SECS - 0.1);
+        statuses.apply(StatusEffect::Freeze);
+        statuses.tick(0.2);
+        assert!(!statuses.has(StatusEffect::Freeze));
+
+        // immune right after thawing
+        statuses.apply(StatusEffect::Freeze);
+        assert!(!statuses.has(StatusEffect::Freeze));
+        statuses.tick(FREEZE_IMMUNITY_SECS);
+        statuses.apply(StatusEffect::Freeze);
+        assert!(!statuses.can_act());
+    }
+}